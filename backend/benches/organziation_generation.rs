@@ -14,12 +14,12 @@ fn create_dummy_teachers(count: u16) -> Result<Arc<[Teacher]>> {
     Ok(teachers)
 }
 
-fn create_dummy_teacher_groupings(count: u16) -> Result<Arc<[Arc<[Teacher]>]>> {
+fn create_dummy_teacher_groupings(count: u16) -> Result<Arc<[TeacherGrouping]>> {
     let teachers = create_dummy_teachers(count * 2)?;
     let teacher_groupings = teachers
         .chunks(2)
-        .map(|c| c.to_vec().into())
-        .collect::<Arc<[Arc<[_]>]>>();
+        .map(|c| TeacherGrouping::new(c.to_vec()))
+        .collect::<Arc<[_]>>();
 
     Ok(teacher_groupings)
 }
@@ -40,9 +40,9 @@ fn create_dummy_students(count: u16) -> Result<Arc<[Student]>> {
 
 pub fn generation_speed_benchmark_5(c: &mut Criterion) {
     let settings = Settings {
-        weights: Weights::default(),
         num_advisories: 5,
         teacher_groupings: create_dummy_teacher_groupings(5).unwrap(),
+        ..Default::default()
     };
 
     for student_count in [100, 500, 1000] {
@@ -60,9 +60,9 @@ pub fn generation_speed_benchmark_5(c: &mut Criterion) {
 
 pub fn generation_speed_benchmark_20(c: &mut Criterion) {
     let settings = Settings {
-        weights: Weights::default(),
         num_advisories: 20,
         teacher_groupings: create_dummy_teacher_groupings(20).unwrap(),
+        ..Default::default()
     };
 
     for student_count in [100, 500, 1000] {
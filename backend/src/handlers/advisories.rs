@@ -1,8 +1,11 @@
 use crate::{auth::UserData, SharedState};
 use advisory_backend_lib::{
-    advisories::{Organization, Settings},
+    advisories::{
+        BestOrganization, Organization, Regeneration, RegenerationForm, Settings, TeacherGrouping,
+    },
     people::Student,
-    DatabaseNode, Verify,
+    storage::Repository,
+    Error, Verify,
 };
 use axum::{
     extract::{Extension, Json, State},
@@ -10,24 +13,82 @@ use axum::{
 };
 use std::sync::Arc;
 
+/// Group the account's teachers into advisors if the settings don't have any groupings
+///
+/// Returns the groupings that were formed, so they can be sent back along with the organization
+async fn resolve_teacher_groupings(
+    repository: &dyn Repository,
+    user: &UserData,
+    settings: &mut Settings,
+    students: &[Student],
+) -> Result<Option<Arc<[TeacherGrouping]>>, Error> {
+    if !settings.teacher_groupings.is_empty() {
+        return Ok(None);
+    }
+    let teachers = repository.get_teachers(user.user_id()).await?;
+    let bans = repository.get_coadvisor_bans(user.user_id()).await?;
+    let groupings = TeacherGrouping::form(&teachers, students, settings.num_advisories, &bans)?;
+    settings.teacher_groupings = groupings.clone();
+    Ok(Some(groupings))
+}
+
 /// Get list of populated advisories based around passed settings and database values
+///
+/// Advisors are grouped automatically if the settings don't have any groupings
 #[axum_macros::debug_handler]
 pub(crate) async fn get_advisories(
     State(state): State<SharedState>,
     Extension(user_option): Extension<Option<UserData>>,
-    Json(form): Json<Settings>,
-) -> Result<Json<Organization>, StatusCode> {
+    Json(mut form): Json<Settings>,
+) -> Result<Json<BestOrganization>, Error> {
     if let Some(user) = user_option {
+        let students: Arc<[Student]> = state.repository.get_students(user.user_id()).await?;
+        let formed =
+            resolve_teacher_groupings(&*state.repository, &user, &mut form, &students).await?;
         form.verify()?;
-        match &state.graph {
-            Some(graph) => {
-                let students: Arc<[Student]> = Student::get_nodes(graph, user.user_id()).await?;
-                Ok(Json(Organization::generate(&form, students).await?))
-            }
-            None => Err(StatusCode::BAD_GATEWAY),
+        if form.grade_levels.is_none() {
+            form.grade_levels = Some(state.repository.get_grade_levels(user.user_id()).await?);
+        }
+        if form.groups.is_none() {
+            form.groups = Some(state.repository.get_groups(user.user_id()).await?);
         }
+        let mut generated = Organization::generate_best(&form, students).await?;
+        generated.teacher_groupings = formed;
+        Ok(Json(generated))
     } else {
         log::info!("Unauthorized access to get_advisories prevented");
-        Err(StatusCode::UNAUTHORIZED)
+        Err(StatusCode::UNAUTHORIZED.into())
+    }
+}
+
+/// Regenerate advisories from a previous organization, keeping students where they were when possible
+///
+/// Advisors are grouped automatically if the settings don't have any groupings
+#[axum_macros::debug_handler]
+pub(crate) async fn regenerate_advisories(
+    State(state): State<SharedState>,
+    Extension(user_option): Extension<Option<UserData>>,
+    Json(mut form): Json<RegenerationForm>,
+) -> Result<Json<Regeneration>, Error> {
+    if let Some(user) = user_option {
+        let students: Arc<[Student]> = state.repository.get_students(user.user_id()).await?;
+        let formed =
+            resolve_teacher_groupings(&*state.repository, &user, &mut form.settings, &students)
+                .await?;
+        form.settings.verify()?;
+        if form.settings.grade_levels.is_none() {
+            form.settings.grade_levels =
+                Some(state.repository.get_grade_levels(user.user_id()).await?);
+        }
+        if form.settings.groups.is_none() {
+            form.settings.groups = Some(state.repository.get_groups(user.user_id()).await?);
+        }
+        let mut regenerated =
+            Organization::regenerate(&form.settings, &form.previous, students).await?;
+        regenerated.generated.teacher_groupings = formed;
+        Ok(Json(regenerated))
+    } else {
+        log::info!("Unauthorized access to regenerate_advisories prevented");
+        Err(StatusCode::UNAUTHORIZED.into())
     }
 }
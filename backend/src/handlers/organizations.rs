@@ -0,0 +1,184 @@
+use crate::{auth::UserData, SharedState};
+use advisory_backend_lib::{
+    advisories::{
+        DiffForm, Edit, EditResult, Organization, OrganizationDiff, OrganizationSource, RenameForm,
+        SaveForm, SavedOrganization, VersionInfo, Weights,
+    },
+    storage::Repository,
+    Error, Verify,
+};
+use axum::{
+    extract::{Extension, Json, Path, State},
+    http::StatusCode,
+};
+use std::sync::Arc;
+
+/// Handler to list the saved organizations of a specific user, newest first
+#[axum_macros::debug_handler]
+pub(crate) async fn list_organizations(
+    State(state): State<SharedState>,
+    Extension(user_option): Extension<Option<UserData>>,
+) -> Result<Json<Arc<[VersionInfo]>>, Error> {
+    if let Some(user) = user_option {
+        Ok(Json(
+            state.repository.list_organizations(user.user_id()).await?,
+        ))
+    } else {
+        log::info!("Unauthorized access to list_organizations prevented");
+        Err(StatusCode::UNAUTHORIZED.into())
+    }
+}
+
+/// Handler to save an organization under a name
+#[axum_macros::debug_handler]
+pub(crate) async fn save_organization(
+    State(state): State<SharedState>,
+    Extension(user_option): Extension<Option<UserData>>,
+    Json(form): Json<SaveForm>,
+) -> Result<Json<VersionInfo>, Error> {
+    if let Some(user) = user_option {
+        form.verify()?;
+        Ok(Json(
+            state
+                .repository
+                .save_organization(
+                    user.user_id(),
+                    form.name,
+                    &form.settings,
+                    &form.organization,
+                )
+                .await?,
+        ))
+    } else {
+        log::info!("Unauthorized access to save_organization prevented");
+        Err(StatusCode::UNAUTHORIZED.into())
+    }
+}
+
+/// Handler to fetch a saved organization along with the settings it was generated with
+#[axum_macros::debug_handler]
+pub(crate) async fn get_organization(
+    State(state): State<SharedState>,
+    Extension(user_option): Extension<Option<UserData>>,
+    Path(id): Path<String>,
+) -> Result<Json<SavedOrganization>, Error> {
+    if let Some(user) = user_option {
+        Ok(Json(
+            state
+                .repository
+                .get_organization(user.user_id(), &id)
+                .await?,
+        ))
+    } else {
+        log::info!("Unauthorized access to get_organization prevented");
+        Err(StatusCode::UNAUTHORIZED.into())
+    }
+}
+
+/// Handler to edit a saved organization by hand, returning its recalculated score and any violations
+#[axum_macros::debug_handler]
+pub(crate) async fn edit_organization(
+    State(state): State<SharedState>,
+    Extension(user_option): Extension<Option<UserData>>,
+    Path(id): Path<String>,
+    Json(edit): Json<Edit>,
+) -> Result<Json<EditResult>, Error> {
+    if let Some(user) = user_option {
+        let saved = state
+            .repository
+            .get_organization(user.user_id(), &id)
+            .await?;
+        let result = saved.organization.edit(&edit, &saved.settings.weights)?;
+        state
+            .repository
+            .update_organization(user.user_id(), &id, &result.organization)
+            .await?;
+        Ok(Json(result))
+    } else {
+        log::info!("Unauthorized access to edit_organization prevented");
+        Err(StatusCode::UNAUTHORIZED.into())
+    }
+}
+
+/// Handler to rename a saved organization
+#[axum_macros::debug_handler]
+pub(crate) async fn rename_organization(
+    State(state): State<SharedState>,
+    Extension(user_option): Extension<Option<UserData>>,
+    Path(id): Path<String>,
+    Json(form): Json<RenameForm>,
+) -> Result<Json<VersionInfo>, Error> {
+    if let Some(user) = user_option {
+        form.verify()?;
+        Ok(Json(
+            state
+                .repository
+                .rename_organization(user.user_id(), &id, form.name)
+                .await?,
+        ))
+    } else {
+        log::info!("Unauthorized access to rename_organization prevented");
+        Err(StatusCode::UNAUTHORIZED.into())
+    }
+}
+
+/// Handler to delete a saved organization
+#[axum_macros::debug_handler]
+pub(crate) async fn delete_organization(
+    State(state): State<SharedState>,
+    Extension(user_option): Extension<Option<UserData>>,
+    Path(id): Path<String>,
+) -> Result<Json<u8>, Error> {
+    if let Some(user) = user_option {
+        Ok(Json(
+            state
+                .repository
+                .delete_organization(user.user_id(), &id)
+                .await?,
+        ))
+    } else {
+        log::info!("Unauthorized access to delete_organization prevented");
+        Err(StatusCode::UNAUTHORIZED.into())
+    }
+}
+
+/// Get an organization to compare along with the weights to score it with, fetching both from the
+/// database if it was saved
+///
+/// Submitted organizations are scored with the request's weights
+async fn resolve_organization(
+    repository: &dyn Repository,
+    user: &UserData,
+    source: OrganizationSource,
+    weights: &Weights,
+) -> Result<(Organization, Weights), Error> {
+    match source {
+        OrganizationSource::Saved { id } => {
+            let saved = repository.get_organization(user.user_id(), &id).await?;
+            Ok((saved.organization, saved.settings.weights))
+        }
+        OrganizationSource::Submitted(organization) => Ok((organization, weights.clone())),
+    }
+}
+
+/// Handler to compare two organizations, each either saved or sent with the request
+///
+/// Saved organizations are scored with the weights they were saved with
+#[axum_macros::debug_handler]
+pub(crate) async fn diff_organizations(
+    State(state): State<SharedState>,
+    Extension(user_option): Extension<Option<UserData>>,
+    Json(form): Json<DiffForm>,
+) -> Result<Json<OrganizationDiff>, Error> {
+    if let Some(user) = user_option {
+        form.weights.verify()?;
+        let (before, before_weights) =
+            resolve_organization(&*state.repository, &user, form.before, &form.weights).await?;
+        let (after, after_weights) =
+            resolve_organization(&*state.repository, &user, form.after, &form.weights).await?;
+        Ok(Json(before.diff(&after, &before_weights, &after_weights)))
+    } else {
+        log::info!("Unauthorized access to diff_organizations prevented");
+        Err(StatusCode::UNAUTHORIZED.into())
+    }
+}
@@ -1,10 +1,10 @@
 use crate::{auth::UserData, SharedState};
 use advisory_backend_lib::{
-    people::{Person, Student, Teacher},
-    DatabaseNode, Verify,
+    people::{AdvisorPreference, GradeLevels, Group, Person, Preference, Student, Teacher},
+    Error, Verify,
 };
 use axum::{
-    extract::{Extension, Json, State},
+    extract::{Extension, Json, Path, State},
     http::StatusCode,
 };
 use std::sync::Arc;
@@ -14,15 +14,12 @@ use std::sync::Arc;
 pub(crate) async fn clear_people_handler(
     State(state): State<SharedState>,
     Extension(user_option): Extension<Option<UserData>>,
-) -> Result<Json<u8>, StatusCode> {
+) -> Result<Json<u8>, Error> {
     if let Some(user) = user_option {
-        match &state.graph {
-            Some(graph) => Ok(Json(Person::clear_nodes(graph, user.user_id()).await?)),
-            None => Err(StatusCode::BAD_GATEWAY),
-        }
+        Ok(Json(state.repository.clear_people(user.user_id()).await?))
     } else {
         log::info!("Unauthorized access to clear_people_handler prevented");
-        Err(StatusCode::UNAUTHORIZED)
+        Err(StatusCode::UNAUTHORIZED.into())
     }
 }
 
@@ -31,15 +28,12 @@ pub(crate) async fn clear_people_handler(
 pub(crate) async fn get_people_handler(
     State(state): State<SharedState>,
     Extension(user_option): Extension<Option<UserData>>,
-) -> Result<Json<Arc<[Person]>>, StatusCode> {
+) -> Result<Json<Arc<[Person]>>, Error> {
     if let Some(user) = user_option {
-        match &state.graph {
-            Some(graph) => Ok(Json(Person::get_nodes(graph, user.user_id()).await?)),
-            None => Err(StatusCode::BAD_GATEWAY),
-        }
+        Ok(Json(state.repository.get_people(user.user_id()).await?))
     } else {
         log::info!("Unauthorized access to get_people_handler prevented");
-        Err(StatusCode::UNAUTHORIZED)
+        Err(StatusCode::UNAUTHORIZED.into())
     }
 }
 
@@ -48,15 +42,12 @@ pub(crate) async fn get_people_handler(
 pub(crate) async fn get_teachers_handler(
     State(state): State<SharedState>,
     Extension(user_option): Extension<Option<UserData>>,
-) -> Result<Json<Arc<[Teacher]>>, StatusCode> {
+) -> Result<Json<Arc<[Teacher]>>, Error> {
     if let Some(user) = user_option {
-        match &state.graph {
-            Some(graph) => Ok(Json(Teacher::get_nodes(graph, user.user_id()).await?)),
-            None => Err(StatusCode::BAD_GATEWAY),
-        }
+        Ok(Json(state.repository.get_teachers(user.user_id()).await?))
     } else {
         log::info!("Unauthorized access to get_teachers_handler prevented");
-        Err(StatusCode::UNAUTHORIZED)
+        Err(StatusCode::UNAUTHORIZED.into())
     }
 }
 
@@ -65,15 +56,12 @@ pub(crate) async fn get_teachers_handler(
 pub(crate) async fn get_students_handler(
     State(state): State<SharedState>,
     Extension(user_option): Extension<Option<UserData>>,
-) -> Result<Json<Arc<[Student]>>, StatusCode> {
+) -> Result<Json<Arc<[Student]>>, Error> {
     if let Some(user) = user_option {
-        match &state.graph {
-            Some(graph) => Ok(Json(Student::get_nodes(graph, user.user_id()).await?)),
-            None => Err(StatusCode::BAD_GATEWAY),
-        }
+        Ok(Json(state.repository.get_students(user.user_id()).await?))
     } else {
         log::info!("Unauthorized access to get_students_handler prevented");
-        Err(StatusCode::UNAUTHORIZED)
+        Err(StatusCode::UNAUTHORIZED.into())
     }
 }
 
@@ -85,16 +73,18 @@ pub(crate) async fn add_teacher_handler(
     State(state): State<SharedState>,
     Extension(user_option): Extension<Option<UserData>>,
     Json(form): Json<Teacher>,
-) -> Result<Json<u8>, StatusCode> {
+) -> Result<Json<u8>, Error> {
     if let Some(user) = user_option {
         form.verify()?;
-        match &state.graph {
-            Some(graph) => Ok(Json(form.add_node(graph, user.user_id(), true).await?)),
-            None => Err(StatusCode::BAD_GATEWAY),
-        }
+        Ok(Json(
+            state
+                .repository
+                .add_teachers(user.user_id(), std::slice::from_ref(&form))
+                .await?,
+        ))
     } else {
         log::info!("Unauthorized access to add_teacher_handler prevented");
-        Err(StatusCode::UNAUTHORIZED)
+        Err(StatusCode::UNAUTHORIZED.into())
     }
 }
 
@@ -106,18 +96,15 @@ pub(crate) async fn add_teacher_bulk(
     State(state): State<SharedState>,
     Extension(user_option): Extension<Option<UserData>>,
     Json(form): Json<Arc<[Teacher]>>,
-) -> Result<Json<u8>, StatusCode> {
+) -> Result<Json<u8>, Error> {
     if let Some(user) = user_option {
         form.verify()?;
-        match &state.graph {
-            Some(graph) => Ok(Json(
-                Teacher::add_multiple_nodes(&form, graph, user.user_id(), true).await?,
-            )),
-            None => Err(StatusCode::BAD_GATEWAY),
-        }
+        Ok(Json(
+            state.repository.add_teachers(user.user_id(), &form).await?,
+        ))
     } else {
         log::info!("Unauthorized access to add_teacher_bulk prevented");
-        Err(StatusCode::UNAUTHORIZED)
+        Err(StatusCode::UNAUTHORIZED.into())
     }
 }
 
@@ -128,17 +115,23 @@ pub(crate) async fn add_teacher_bulk(
 pub(crate) async fn add_student_handler(
     State(state): State<SharedState>,
     Extension(user_option): Extension<Option<UserData>>,
-    Json(form): Json<Student>,
-) -> Result<Json<u8>, StatusCode> {
+    Json(mut form): Json<Student>,
+) -> Result<Json<u8>, Error> {
     if let Some(user) = user_option {
         form.verify()?;
-        match &state.graph {
-            Some(graph) => Ok(Json(form.add_node(graph, user.user_id(), true).await?)),
-            None => Err(StatusCode::BAD_GATEWAY),
-        }
+        let teachers = state.repository.get_teachers(user.user_id()).await?;
+        form.move_advisor_bans(&teachers);
+        let levels = state.repository.get_grade_levels(user.user_id()).await?;
+        levels.check_grade(&form)?;
+        Ok(Json(
+            state
+                .repository
+                .add_students(user.user_id(), std::slice::from_ref(&form))
+                .await?,
+        ))
     } else {
         log::info!("Unauthorized access to add_student_handler prevented");
-        Err(StatusCode::UNAUTHORIZED)
+        Err(StatusCode::UNAUTHORIZED.into())
     }
 }
 
@@ -150,18 +143,22 @@ pub(crate) async fn add_student_bulk(
     State(state): State<SharedState>,
     Extension(user_option): Extension<Option<UserData>>,
     Json(form): Json<Arc<[Student]>>,
-) -> Result<Json<u8>, StatusCode> {
+) -> Result<Json<u8>, Error> {
     if let Some(user) = user_option {
         form.verify()?;
-        match &state.graph {
-            Some(graph) => Ok(Json(
-                Student::add_multiple_nodes(&form, graph, user.user_id(), true).await?,
-            )),
-            None => Err(StatusCode::BAD_GATEWAY),
+        let teachers = state.repository.get_teachers(user.user_id()).await?;
+        let mut form = form.to_vec();
+        for student in form.iter_mut() {
+            student.move_advisor_bans(&teachers);
         }
+        let levels = state.repository.get_grade_levels(user.user_id()).await?;
+        levels.check_grades(&form)?;
+        Ok(Json(
+            state.repository.add_students(user.user_id(), &form).await?,
+        ))
     } else {
         log::info!("Unauthorized access to add_student_bulk prevented");
-        Err(StatusCode::UNAUTHORIZED)
+        Err(StatusCode::UNAUTHORIZED.into())
     }
 }
 
@@ -171,16 +168,214 @@ pub(crate) async fn ban_pair_handler(
     State(state): State<SharedState>,
     Extension(user_option): Extension<Option<UserData>>,
     Json(form): Json<[Person; 2]>,
-) -> Result<Json<u8>, StatusCode> {
+) -> Result<Json<u8>, Error> {
     if let Some(user) = user_option {
-        match &state.graph {
-            Some(graph) => Ok(Json(
-                Person::ban_pair(form, graph, user.user_id(), true).await?,
-            )),
-            None => Err(StatusCode::BAD_GATEWAY),
-        }
+        Ok(Json(state.repository.ban_pair(user.user_id(), form).await?))
     } else {
         log::info!("Unauthorized access to ban_pair_handler prevented");
-        Err(StatusCode::UNAUTHORIZED)
+        Err(StatusCode::UNAUTHORIZED.into())
+    }
+}
+
+/// Handler to record a student's request to be placed with a peer
+#[axum_macros::debug_handler]
+pub(crate) async fn add_preference_handler(
+    State(state): State<SharedState>,
+    Extension(user_option): Extension<Option<UserData>>,
+    Json(form): Json<Preference>,
+) -> Result<Json<u8>, Error> {
+    if let Some(user) = user_option {
+        form.verify()?;
+        Ok(Json(
+            state
+                .repository
+                .add_preference(user.user_id(), &form)
+                .await?,
+        ))
+    } else {
+        log::info!("Unauthorized access to add_preference_handler prevented");
+        Err(StatusCode::UNAUTHORIZED.into())
+    }
+}
+
+/// Handler to list every request from a specific user's students to be placed with a peer
+#[axum_macros::debug_handler]
+pub(crate) async fn get_preferences_handler(
+    State(state): State<SharedState>,
+    Extension(user_option): Extension<Option<UserData>>,
+) -> Result<Json<Arc<[Preference]>>, Error> {
+    if let Some(user) = user_option {
+        Ok(Json(
+            state.repository.get_preferences(user.user_id()).await?,
+        ))
+    } else {
+        log::info!("Unauthorized access to get_preferences_handler prevented");
+        Err(StatusCode::UNAUTHORIZED.into())
+    }
+}
+
+/// Handler to withdraw a student's request to be placed with a peer
+#[axum_macros::debug_handler]
+pub(crate) async fn remove_preference_handler(
+    State(state): State<SharedState>,
+    Extension(user_option): Extension<Option<UserData>>,
+    Json(form): Json<Preference>,
+) -> Result<Json<u8>, Error> {
+    if let Some(user) = user_option {
+        Ok(Json(
+            state
+                .repository
+                .remove_preference(user.user_id(), &form)
+                .await?,
+        ))
+    } else {
+        log::info!("Unauthorized access to remove_preference_handler prevented");
+        Err(StatusCode::UNAUTHORIZED.into())
+    }
+}
+
+/// Handler to record a student's request to be advised by a particular teacher
+#[axum_macros::debug_handler]
+pub(crate) async fn add_advisor_preference_handler(
+    State(state): State<SharedState>,
+    Extension(user_option): Extension<Option<UserData>>,
+    Json(form): Json<AdvisorPreference>,
+) -> Result<Json<u8>, Error> {
+    if let Some(user) = user_option {
+        form.verify()?;
+        Ok(Json(
+            state
+                .repository
+                .add_advisor_preference(user.user_id(), &form)
+                .await?,
+        ))
+    } else {
+        log::info!("Unauthorized access to add_advisor_preference_handler prevented");
+        Err(StatusCode::UNAUTHORIZED.into())
+    }
+}
+
+/// Handler to list every request from a specific user's students to be advised by a particular teacher
+#[axum_macros::debug_handler]
+pub(crate) async fn get_advisor_preferences_handler(
+    State(state): State<SharedState>,
+    Extension(user_option): Extension<Option<UserData>>,
+) -> Result<Json<Arc<[AdvisorPreference]>>, Error> {
+    if let Some(user) = user_option {
+        Ok(Json(
+            state
+                .repository
+                .get_advisor_preferences(user.user_id())
+                .await?,
+        ))
+    } else {
+        log::info!("Unauthorized access to get_advisor_preferences_handler prevented");
+        Err(StatusCode::UNAUTHORIZED.into())
+    }
+}
+
+/// Handler to withdraw a student's request to be advised by a particular teacher
+#[axum_macros::debug_handler]
+pub(crate) async fn remove_advisor_preference_handler(
+    State(state): State<SharedState>,
+    Extension(user_option): Extension<Option<UserData>>,
+    Json(form): Json<AdvisorPreference>,
+) -> Result<Json<u8>, Error> {
+    if let Some(user) = user_option {
+        Ok(Json(
+            state
+                .repository
+                .remove_advisor_preference(user.user_id(), &form)
+                .await?,
+        ))
+    } else {
+        log::info!("Unauthorized access to remove_advisor_preference_handler prevented");
+        Err(StatusCode::UNAUTHORIZED.into())
+    }
+}
+
+/// Handler to save a group of students that are always placed together
+#[axum_macros::debug_handler]
+pub(crate) async fn set_group_handler(
+    State(state): State<SharedState>,
+    Extension(user_option): Extension<Option<UserData>>,
+    Json(form): Json<Group>,
+) -> Result<Json<u8>, Error> {
+    if let Some(user) = user_option {
+        form.verify()?;
+        Ok(Json(
+            state.repository.set_group(user.user_id(), &form).await?,
+        ))
+    } else {
+        log::info!("Unauthorized access to set_group_handler prevented");
+        Err(StatusCode::UNAUTHORIZED.into())
+    }
+}
+
+/// Handler to list the groups of students that a specific user keeps together
+#[axum_macros::debug_handler]
+pub(crate) async fn get_groups_handler(
+    State(state): State<SharedState>,
+    Extension(user_option): Extension<Option<UserData>>,
+) -> Result<Json<Arc<[Group]>>, Error> {
+    if let Some(user) = user_option {
+        Ok(Json(state.repository.get_groups(user.user_id()).await?))
+    } else {
+        log::info!("Unauthorized access to get_groups_handler prevented");
+        Err(StatusCode::UNAUTHORIZED.into())
+    }
+}
+
+/// Handler to delete a group of students, leaving the students themselves
+#[axum_macros::debug_handler]
+pub(crate) async fn remove_group_handler(
+    State(state): State<SharedState>,
+    Extension(user_option): Extension<Option<UserData>>,
+    Path(name): Path<String>,
+) -> Result<Json<u8>, Error> {
+    if let Some(user) = user_option {
+        Ok(Json(
+            state.repository.remove_group(user.user_id(), &name).await?,
+        ))
+    } else {
+        log::info!("Unauthorized access to remove_group_handler prevented");
+        Err(StatusCode::UNAUTHORIZED.into())
+    }
+}
+
+/// Handler to get the grade levels that a specific user's students can be in
+#[axum_macros::debug_handler]
+pub(crate) async fn get_grade_levels_handler(
+    State(state): State<SharedState>,
+    Extension(user_option): Extension<Option<UserData>>,
+) -> Result<Json<GradeLevels>, Error> {
+    if let Some(user) = user_option {
+        Ok(Json(
+            state.repository.get_grade_levels(user.user_id()).await?,
+        ))
+    } else {
+        log::info!("Unauthorized access to get_grade_levels_handler prevented");
+        Err(StatusCode::UNAUTHORIZED.into())
+    }
+}
+
+/// Handler to set the grade levels that a specific user's students can be in
+#[axum_macros::debug_handler]
+pub(crate) async fn set_grade_levels_handler(
+    State(state): State<SharedState>,
+    Extension(user_option): Extension<Option<UserData>>,
+    Json(form): Json<GradeLevels>,
+) -> Result<Json<u8>, Error> {
+    if let Some(user) = user_option {
+        form.verify()?;
+        Ok(Json(
+            state
+                .repository
+                .set_grade_levels(user.user_id(), &form)
+                .await?,
+        ))
+    } else {
+        log::info!("Unauthorized access to set_grade_levels_handler prevented");
+        Err(StatusCode::UNAUTHORIZED.into())
     }
 }
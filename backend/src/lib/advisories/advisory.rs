@@ -1,9 +1,43 @@
 use crate::{
-    advisories::Weights,
-    people::{Grade, Sex, Student, Teacher},
+    advisories::{Score, Weights},
+    people::{Grade, GradeLevels, Student, Teacher, GENDER},
 };
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
+
+/// A quota in an [`Advisory`] that placed students take up
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum Quota {
+    /// Quota for a value of a balanced attribute, by the positions of the attribute and of the value
+    Attribute(usize, usize),
+    /// Quota for a [`Grade`], by its position in the grade levels
+    Grade(usize),
+    /// Overall person quota
+    People,
+}
+
+/// Quotas for the values of an attribute that is balanced across advisories
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub(crate) struct AttributeQuota {
+    /// Name of the attribute
+    pub(crate) name: Arc<str>,
+    /// Values of the attribute, with students that have no value left out of the quotas
+    pub(crate) values: Vec<Arc<str>>,
+    /// Remaining quota for each value, in the same order as `values`
+    pub(crate) remaining: Vec<i16>,
+}
+
+impl AttributeQuota {
+    /// Position of a student's value for the attribute, or none if the student has no value or it isn't
+    /// one of the values
+    fn value_index(&self, s: &Student) -> Option<usize> {
+        let value = s.attributes.get(&self.name)?;
+        self.values
+            .iter()
+            .position(|v| v == value)
+            .filter(|&index| index < self.remaining.len())
+    }
+}
 
 /// Representation of an advisory
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -12,17 +46,25 @@ pub struct Advisory {
     advisors: Vec<Teacher>,
     /// Vector of [`Student`] structs
     students: Vec<Student>,
-    /// Remaining quota for each [`Sex`]
-    ///
-    /// Represents (Male, Female)
-    remaining_sex: [i16; 2],
-    /// Remaining quota for each [`Grade`]
-    ///
-    /// Represents (Freshman, Sophomore, Junior, Senior)
-    remaining_grade: [i16; 4],
+    /// Remaining quota for each value of every attribute balanced across advisories
+    #[serde(default)]
+    remaining_attributes: Vec<AttributeQuota>,
+    /// Remaining quota for each [`Grade`], in the same order as `grade_levels`
+    #[serde(default)]
+    remaining_grade: Vec<i16>,
+    /// Grade levels that the grade quotas are for
+    #[serde(default)]
+    grade_levels: GradeLevels,
     /// Remaining person quota overall
     /// Used for remaining people weighted value
+    #[serde(default)]
     remaining_people: i16,
+    /// Fewest students the advisory may end up with
+    #[serde(default)]
+    min_students: u16,
+    /// Most students the advisory may hold, if limited
+    #[serde(default)]
+    max_students: Option<u16>,
 }
 
 impl std::fmt::Display for Advisory {
@@ -42,56 +84,155 @@ impl std::fmt::Display for Advisory {
 }
 
 impl Advisory {
-    /// Default advisory values given target number of students for the advisory
-    pub(crate) fn new(n: u16) -> Self {
-        let signed = n as i16;
+    /// Empty advisory with the given person quota, quotas for each balanced attribute and [`Grade`], and
+    /// limits on its number of students
+    ///
+    /// Grade quotas are in the order of the levels
+    pub(crate) fn new(
+        people: i16,
+        attributes: Vec<AttributeQuota>,
+        grade: Vec<i16>,
+        grade_levels: GradeLevels,
+        min_students: u16,
+        max_students: Option<u16>,
+    ) -> Self {
         Self {
             advisors: Vec::<Teacher>::with_capacity(2),
-            students: Vec::<Student>::with_capacity(n as usize),
-            // Set number of "spots" based on number of students in advisory
-            remaining_sex: [signed / 2, signed / 2],
-            remaining_grade: [signed / 4, signed / 4, signed / 4, signed / 4],
-            remaining_people: signed,
+            students: Vec::<Student>::with_capacity(people.max(0) as usize),
+            remaining_attributes: attributes,
+            remaining_grade: grade,
+            grade_levels,
+            remaining_people: people,
+            min_students,
+            max_students,
         }
     }
 
+    /// Position of a [`Grade`] in the grade quotas, or none if it isn't one of the advisory's grade levels
+    pub(crate) fn grade_index(&self, grade: &Grade) -> Option<usize> {
+        // advisories sent without their quotas have no grade quotas to take up
+        self.grade_levels
+            .index_of(grade)
+            .filter(|&index| index < self.remaining_grade.len())
+    }
+
     /// Adds a [`Student`] struct to the students vector
     pub(crate) fn add_student(&mut self, s: Student) {
-        // Reduce sex quota for the added student's sex
-        match s.sex {
-            Some(Sex::Male) => self.remaining_sex[0] -= 1,
-            Some(Sex::Female) => self.remaining_sex[1] -= 1,
-            None => {}
+        self.reserve_quotas(&s);
+        self.students.push(s);
+    }
+
+    /// Reduces the quotas that a [`Student`] takes up when placed in the advisory
+    fn reserve_quotas(&mut self, s: &Student) {
+        // Reduce attribute quotas for the added student's values
+        for quota in self.remaining_attributes.iter_mut() {
+            if let Some(index) = quota.value_index(s) {
+                quota.remaining[index] -= 1;
+            }
         }
         // Reduce grade quota for the added student's grade
-        match s.grade {
-            Grade::Freshman => self.remaining_grade[0] -= 1,
-            Grade::Sophomore => self.remaining_grade[1] -= 1,
-            Grade::Junior => self.remaining_grade[2] -= 1,
-            Grade::Senior => self.remaining_grade[3] -= 1,
+        if let Some(index) = self.grade_index(&s.grade) {
+            self.remaining_grade[index] -= 1;
         }
         // Reduce remaining people quota
         self.remaining_people -= 1;
+    }
 
-        self.students.push(s);
+    /// Removes the [`Student`] at the given index from the students vector and returns it
+    pub(crate) fn remove_student(&mut self, index: usize) -> Student {
+        let s = self.students.remove(index);
+        self.release_quotas(&s);
+        s
     }
 
-    /// Gets the remaining quota for a given sex in an advisory
-    pub(crate) fn get_remaining_sex(&self, sex: &Option<Sex>) -> i16 {
-        match sex {
-            Some(Sex::Male) => self.remaining_sex[0],
-            Some(Sex::Female) => self.remaining_sex[1],
-            None => 0,
+    /// Gives back the quotas that a [`Student`] took up when placed in the advisory
+    fn release_quotas(&mut self, s: &Student) {
+        // Return attribute quotas for the removed student's values
+        for quota in self.remaining_attributes.iter_mut() {
+            if let Some(index) = quota.value_index(s) {
+                quota.remaining[index] += 1;
+            }
         }
+        // Return grade quota for the removed student's grade
+        if let Some(index) = self.grade_index(&s.grade) {
+            self.remaining_grade[index] += 1;
+        }
+        // Return remaining people quota
+        self.remaining_people += 1;
+    }
+
+    /// Gets the [`Student`] at the given index of the students vector
+    pub(crate) fn get_student(&self, index: usize) -> &Student {
+        &self.students[index]
+    }
+
+    /// Gets the students currently placed in the advisory
+    pub(crate) fn students(&self) -> &[Student] {
+        &self.students
+    }
+
+    /// Gets the advisors of the advisory
+    pub(crate) fn advisors(&self) -> &[Teacher] {
+        &self.advisors
+    }
+
+    /// Gets the number of students currently placed in the advisory
+    pub(crate) fn student_count(&self) -> usize {
+        self.students.len()
+    }
+
+    /// Checks whether another student can be placed without going over the advisory's size limit
+    pub(crate) fn has_room(&self) -> bool {
+        self.has_room_for(1)
+    }
+
+    /// Checks whether `count` more students can be placed without going over the advisory's size limit
+    pub(crate) fn has_room_for(&self, count: usize) -> bool {
+        match self.max_students {
+            Some(max) => self.students.len() + count <= max as usize,
+            None => true,
+        }
+    }
+
+    /// Gets the number of students still needed to reach the advisory's minimum size
+    pub(crate) fn students_needed(&self) -> usize {
+        (self.min_students as usize).saturating_sub(self.students.len())
+    }
+
+    /// Checks whether a student can be taken out without going under the advisory's minimum size
+    pub(crate) fn can_spare(&self) -> bool {
+        self.students.len() > self.min_students as usize
+    }
+
+    /// Gets the limits on the advisory's number of students
+    pub(crate) fn size_limits(&self) -> (u16, Option<u16>) {
+        (self.min_students, self.max_students)
+    }
+
+    /// Gets the weighted value of the remaining attribute quotas for a student's values, split into gender
+    /// and the other attributes
+    fn attributes_weighted_value(&self, s: &Student, weights: &Weights) -> (i32, i32) {
+        let mut gender = 0;
+        let mut other = 0;
+        for quota in self.remaining_attributes.iter() {
+            if let Some(index) = quota.value_index(s) {
+                let value = quota.values.len() as i32
+                    * (weights.attribute_weight(&quota.name) as i32
+                        * quota.remaining[index] as i32);
+                match &*quota.name {
+                    GENDER => gender += value,
+                    _ => other += value,
+                }
+            }
+        }
+        (gender, other)
     }
 
     /// Gets the remaining quota for a given grade in an advisory
     pub(crate) fn get_remaining_grade(&self, grade: &Grade) -> i16 {
-        match grade {
-            Grade::Freshman => self.remaining_grade[0],
-            Grade::Sophomore => self.remaining_grade[1],
-            Grade::Junior => self.remaining_grade[2],
-            Grade::Senior => self.remaining_grade[3],
+        match self.grade_index(grade) {
+            Some(index) => self.remaining_grade[index],
+            None => 0,
         }
     }
 
@@ -100,11 +241,50 @@ impl Advisory {
         self.remaining_people
     }
 
+    /// Gets the quotas that a student would take up in the advisory, along with the amount remaining
+    /// of each and the weighted multiplier that [`Advisory::calculate_score`] applies to it
+    ///
+    /// Placing `c` students that share a quota adds `multiplier * c * (remaining + 1 - c)` to
+    /// [`Advisory::calculate_total_score`]
+    pub(crate) fn quotas_for(&self, s: &Student, weights: &Weights) -> Vec<(Quota, i16, i32)> {
+        let number_of_grades: i32 = self.remaining_grade.len() as i32;
+
+        let mut quotas = Vec::with_capacity(self.remaining_attributes.len() + 2);
+        for (position, quota) in self.remaining_attributes.iter().enumerate() {
+            if let Some(index) = quota.value_index(s) {
+                quotas.push((
+                    Quota::Attribute(position, index),
+                    quota.remaining[index],
+                    quota.values.len() as i32 * weights.attribute_weight(&quota.name) as i32,
+                ));
+            }
+        }
+        if let Some(index) = self.grade_index(&s.grade) {
+            quotas.push((
+                Quota::Grade(index),
+                self.remaining_grade[index],
+                number_of_grades * weights.grade_diverse as i32,
+            ));
+        }
+        quotas.push((
+            Quota::People,
+            self.remaining_people,
+            2 * weights.equal_people as i32,
+        ));
+        quotas
+    }
+
     /// Adds a [`Teacher`] struct to the advisors vector if Some
     pub(crate) fn add_teacher(&mut self, t: Teacher) {
         self.advisors.push(t);
     }
 
+    /// Removes the advisor with the given name and returns them, if they advise the advisory
+    pub(crate) fn remove_teacher(&mut self, name: &str) -> Option<Teacher> {
+        let index = self.advisors.iter().position(|t| &*t.name == name)?;
+        Some(self.advisors.remove(index))
+    }
+
     /// Checks whether one of the advisors teaches the given student
     pub(crate) fn has_teacher(&self, s: &Student) -> bool {
         let mut has = false;
@@ -114,20 +294,72 @@ impl Advisory {
         has
     }
 
+    /// Checks whether the advisory has at least one of the students that the student asked to be placed with
+    pub(crate) fn has_preferred_pairing(&self, s: &Student) -> bool {
+        self.students
+            .iter()
+            .any(|other| s.preferred_pairings.contains(&other.name))
+    }
+
+    /// Checks whether one of the advisors is a teacher that the student asked to be advised by
+    pub(crate) fn has_preferred_advisor(&self, s: &Student) -> bool {
+        self.advisors
+            .iter()
+            .any(|a| s.preferred_advisors.contains(&a.name))
+    }
+
     /// Checks in the advisory already has a person that is not supposed to be with the student
     pub(crate) fn has_banned_pairing(&self, s: &Student) -> bool {
-        let mut has = false;
-        let advisory_names = self
-            .students
+        self.has_banned_advisor(s) || !self.banned_students_with(s).is_empty()
+    }
+
+    /// Checks whether one of the advisors is not supposed to be with the student
+    ///
+    /// Only banned advisors count. Teachers in a student's banned pairings are moved to their banned advisors
+    /// when the student is added, see [`Student::move_advisor_bans`]
+    pub(crate) fn has_banned_advisor(&self, s: &Student) -> bool {
+        self.advisors
             .iter()
-            .map(|s| s.name.clone())
-            .chain(self.advisors.iter().map(|a| a.name.clone()))
-            .collect::<Vec<Arc<str>>>();
+            .any(|a| s.banned_advisors.contains(&a.name))
+    }
 
-        for banned_name in s.banned_pairings.iter() {
-            has = has || advisory_names.contains(banned_name);
-        }
-        has
+    /// Gets the indices of the students in the advisory that are not supposed to be with the student
+    ///
+    /// A pairing counts if either student lists the other, since students passed in directly rather than
+    /// read from the database may only have one side of the pairing
+    pub(crate) fn banned_students_with(&self, s: &Student) -> Vec<usize> {
+        self.students
+            .iter()
+            .enumerate()
+            .filter(|(_, o)| {
+                s.banned_pairings.contains(&o.name) || o.banned_pairings.contains(&s.name)
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Gets the names of the advisors that are not supposed to advise the student
+    pub(crate) fn banned_advisor_names(&self, s: &Student) -> Vec<Arc<str>> {
+        self.advisors
+            .iter()
+            .filter(|a| s.banned_advisors.contains(&a.name))
+            .map(|a| a.name.clone())
+            .collect()
+    }
+
+    /// Gets the names of the students in the advisory that are not supposed to be with the student
+    pub(crate) fn banned_student_names(&self, s: &Student) -> Vec<Arc<str>> {
+        self.banned_students_with(s)
+            .into_iter()
+            .map(|index| self.students[index].name.clone())
+            .collect()
+    }
+
+    /// Gets the names of everyone in the advisory that is not supposed to be with the student
+    pub(crate) fn banned_names_with(&self, s: &Student) -> Vec<Arc<str>> {
+        let mut names = self.banned_advisor_names(s);
+        names.extend(self.banned_student_names(s));
+        names
     }
 
     /// Calculate a weight between the advisory and a student
@@ -138,23 +370,119 @@ impl Advisory {
         weights: &Weights,
         students_per_advisory: u16,
     ) -> i32 {
-        let number_of_sexes: i32 = self.remaining_sex.len() as i32;
+        self.calculate_score(student, weights, students_per_advisory)
+            .total
+    }
+
+    /// Calculate each weighted term between the advisory and a student
+    /// The total of the returned [`Score`] is the value used by [`Advisory::calculate_weight`]
+    pub(crate) fn calculate_score(
+        &self,
+        student: &Student,
+        weights: &Weights,
+        students_per_advisory: u16,
+    ) -> Score {
         let number_of_grades: i32 = self.remaining_grade.len() as i32;
 
         let teacher_weighted_value = weights.has_teacher as i32
             * students_per_advisory as i32
             * self.has_teacher(student) as i32;
-        let sexes_weighted_value = number_of_sexes
-            * (weights.sex_diverse as i32 * self.get_remaining_sex(&student.sex) as i32);
+        let (sexes_weighted_value, attributes_weighted_value) =
+            self.attributes_weighted_value(student, weights);
+        let criteria_weighted_value = weights
+            .criteria
+            .iter()
+            .map(|c| c.calculate_score(&self.students, student, students_per_advisory))
+            .sum();
         let grade_weighted_value = number_of_grades
             * (weights.grade_diverse as i32 * self.get_remaining_grade(&student.grade) as i32);
         let person_quota_weighted_value =
             2 * weights.equal_people as i32 * self.get_remaining_people() as i32;
         let banned_weighted_value = -10000 * self.has_banned_pairing(student) as i32;
-        teacher_weighted_value
-            + sexes_weighted_value
-            + grade_weighted_value
-            + person_quota_weighted_value
-            + banned_weighted_value
+        let preferred_weighted_value = weights.preferred as i32
+            * students_per_advisory as i32
+            * self.has_preferred_pairing(student) as i32;
+        let preferred_advisor_weighted_value = weights.preferred_advisor as i32
+            * students_per_advisory as i32
+            * self.has_preferred_advisor(student) as i32;
+        Score {
+            teacher: teacher_weighted_value,
+            sex: sexes_weighted_value,
+            attributes: attributes_weighted_value,
+            criteria: criteria_weighted_value,
+            grade: grade_weighted_value,
+            people: person_quota_weighted_value,
+            banned: banned_weighted_value,
+            preferred: preferred_weighted_value,
+            preferred_advisor: preferred_advisor_weighted_value,
+            stability: 0,
+            total: teacher_weighted_value
+                + sexes_weighted_value
+                + attributes_weighted_value
+                + criteria_weighted_value
+                + grade_weighted_value
+                + person_quota_weighted_value
+                + banned_weighted_value
+                + preferred_weighted_value
+                + preferred_advisor_weighted_value,
+        }
+    }
+
+    /// Calculate the weighted value of placing a student in the advisory at `index`
+    ///
+    /// `previous` maps student names to the index of the advisory they were in before regenerating, and only
+    /// students going back to that advisory add any value
+    pub(crate) fn calculate_stability_weight(
+        index: usize,
+        student: &Student,
+        previous: &HashMap<Arc<str>, usize>,
+        weights: &Weights,
+        students_per_advisory: u16,
+    ) -> i32 {
+        weights.stability as i32
+            * students_per_advisory as i32
+            * (previous.get(&student.name) == Some(&index)) as i32
+    }
+
+    /// Calculate the combined [`Advisory::calculate_stability_weight`] of every student in the advisory at
+    /// `index`
+    pub(crate) fn calculate_total_stability(
+        &self,
+        index: usize,
+        previous: &HashMap<Arc<str>, usize>,
+        weights: &Weights,
+        students_per_advisory: u16,
+    ) -> i32 {
+        self.students
+            .iter()
+            .map(|s| {
+                Advisory::calculate_stability_weight(
+                    index,
+                    s,
+                    previous,
+                    weights,
+                    students_per_advisory,
+                )
+            })
+            .sum()
+    }
+
+    /// Calculate the combined score of every student in the advisory
+    /// Each student is scored with [`Advisory::calculate_score`] as if they were the last one placed, so
+    /// the sum can be compared between advisories regardless of the order students were added
+    pub(crate) fn calculate_total_score(
+        &self,
+        weights: &Weights,
+        students_per_advisory: u16,
+    ) -> Score {
+        let mut scratch = self.clone();
+        let mut total = Score::default();
+        for index in 0..self.students.len() {
+            let student = scratch.remove_student(index);
+            total = total + scratch.calculate_score(&student, weights, students_per_advisory);
+            scratch.reserve_quotas(&student);
+            scratch.students.insert(index, student);
+        }
+        total
     }
 }
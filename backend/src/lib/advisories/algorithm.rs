@@ -0,0 +1,333 @@
+use crate::{
+    advisories::{advisory::Quota, Advisory, Settings},
+    people::Student,
+};
+use microlp::{ComparisonOp, OptimizationDirection, Problem, Variable};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    iter::once,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+/// Most student and advisory pairs that [`solve_exact`] builds a program for
+///
+/// A solve can't be interrupted once it starts, so only programs that usually solve within several seconds,
+/// such as 400 students in 15 advisories, are attempted, which bounds how long a solve keeps running after
+/// its deadline. [`Settings::verify_capacity`] rejects the exact algorithm for anything larger.
+pub(crate) const MAX_EXACT_ASSIGNMENTS: usize = 6000;
+
+/// Most exact solves that run at once, including any whose requests already stopped waiting for them
+const MAX_EXACT_SOLVES: usize = 2;
+
+/// Exact solves running now
+static EXACT_SOLVES: AtomicUsize = AtomicUsize::new(0);
+
+/// Claim on one of the [`MAX_EXACT_SOLVES`] running solves, which is given back when dropped
+pub(crate) struct ExactSolve;
+
+impl ExactSolve {
+    /// Claim a running solve, or `None` if as many solves as allowed are already running
+    pub(crate) fn start() -> Option<Self> {
+        EXACT_SOLVES
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |running| {
+                (running < MAX_EXACT_SOLVES).then_some(running + 1)
+            })
+            .ok()
+            .map(|_| Self)
+    }
+}
+
+impl Drop for ExactSolve {
+    fn drop(&mut self) {
+        EXACT_SOLVES.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Algorithm used to place students into advisories
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Algorithm {
+    /// Place students one at a time into whichever advisory suits them best
+    #[default]
+    Greedy,
+    /// Solve for the highest scoring placement possible as an integer program
+    ///
+    /// Used by [`crate::advisories::Organization::generate_best`], which keeps the greedy result if the
+    /// solver doesn't finish within [`crate::advisories::Settings::exact_timeout`]. Too many students for the
+    /// advisories to solve in a few seconds are rejected before generating. Custom
+    /// [`crate::advisories::Criterion`]s aren't quotas, so settings can't use both
+    Exact,
+}
+
+/// Students that share a quota in one advisory, along with the variable for the value they add to the score
+struct QuotaGroup {
+    /// Assignment variables of the students that would take up the quota
+    members: Vec<Variable>,
+    /// Quota remaining before any students are placed
+    remaining: i16,
+    /// Weighted multiplier applied to the quota
+    multiplier: i32,
+    /// Variable holding the value that the group adds to the score
+    value: Variable,
+    /// Number of segments bounding the value, which is exact for up to this many students
+    segments: usize,
+}
+
+impl QuotaGroup {
+    /// Value that the group adds to the score when `count` of its students are placed
+    fn value_at(&self, count: usize) -> f64 {
+        let count = count as f64;
+        self.multiplier as f64 * count * (self.remaining as f64 + 1.0 - count)
+    }
+
+    /// Bound the group's value with the segment between each student count and the next, up to `count`
+    ///
+    /// The value is concave in the number of students, so it is exact wherever a segment covers the count
+    fn add_segments(&mut self, problem: &mut Problem, count: usize) {
+        let count = count.min(self.members.len());
+        for j in self.segments..count {
+            let slope = self.value_at(j + 1) - self.value_at(j);
+            // value - slope * students <= value_at(j) - slope * j
+            let expr = self
+                .members
+                .iter()
+                .map(|&m| (m, -slope))
+                .chain(once((self.value, 1.0)))
+                .collect::<Vec<_>>();
+            problem.add_constraint(
+                expr.as_slice(),
+                ComparisonOp::Le,
+                self.value_at(j) - slope * j as f64,
+            );
+        }
+        self.segments = self.segments.max(count);
+    }
+}
+
+/// Place students with an integer program that maximizes the same score as [`Advisory::calculate_total_score`]
+///
+/// `advisories` should have advisors but no students yet. Students pinned by the settings' pins are only
+/// allowed in the advisory they are pinned to, and the members of each of the settings' groups are always
+/// in the same advisory. Keeping students in the advisory that `previous` maps them to and placing students with a preferred peer
+/// or advisor also count towards the score. Banned pairings and size limits are constraints rather than penalties.
+///
+/// Returns the filled advisories and the highest total score possible, or `None` if the students can't all
+/// be placed, there are more than [`MAX_EXACT_ASSIGNMENTS`] pairs of students and advisories, or the deadline
+/// passes before the solution is proven optimal. The deadline is checked between solves, so the solve in
+/// progress finishes first.
+///
+/// Quota segments are only added around each quota at first, and more are added whenever a solution goes
+/// past them, which keeps the program small without losing exactness.
+///
+/// Custom criteria can't be written as quotas, so `None` is returned if the weights have any, though settings
+/// with both are rejected before generating
+pub(crate) fn solve_exact(
+    advisories: &[Advisory],
+    students: &[Student],
+    settings: &Settings,
+    students_per_advisory: u16,
+    previous: &HashMap<Arc<str>, usize>,
+    deadline: Instant,
+) -> Option<(Vec<Advisory>, i32)> {
+    let weights = &settings.weights;
+    let pinned = settings.pinned_advisories();
+    if !weights.criteria.is_empty() || students.len() * advisories.len() > MAX_EXACT_ASSIGNMENTS {
+        return None;
+    }
+    let mut problem = Problem::new(OptimizationDirection::Maximize);
+
+    // one variable per student per advisory they are allowed to be in, which is only one for pinned students
+    let assignments: Vec<Vec<Option<Variable>>> = students
+        .iter()
+        .map(|s| {
+            let pin = pinned.get(&s.name);
+            advisories
+                .iter()
+                .enumerate()
+                .map(|(index, a)| {
+                    let allowed = match pin {
+                        Some(&pin) => pin == index,
+                        None => true,
+                    };
+                    (allowed && !a.has_banned_advisor(s)).then(|| {
+                        // advisors are already fixed, so these terms only depend on the advisory
+                        let score = a.calculate_score(s, weights, students_per_advisory);
+                        let stability = Advisory::calculate_stability_weight(
+                            index,
+                            s,
+                            previous,
+                            weights,
+                            students_per_advisory,
+                        );
+                        problem.add_binary_var(
+                            (score.teacher + score.preferred_advisor + stability) as f64,
+                        )
+                    })
+                })
+                .collect()
+        })
+        .collect();
+
+    // every student is placed exactly once
+    for row in assignments.iter() {
+        let expr = row.iter().flatten().map(|&v| (v, 1.0)).collect::<Vec<_>>();
+        if expr.is_empty() {
+            return None;
+        }
+        problem.add_constraint(expr.as_slice(), ComparisonOp::Eq, 1.0);
+    }
+
+    // advisories stay within their size limits
+    for (advisory_index, advisory) in advisories.iter().enumerate() {
+        let expr = assignments
+            .iter()
+            .filter_map(|row| row[advisory_index])
+            .map(|v| (v, 1.0))
+            .collect::<Vec<_>>();
+        let (min, max) = advisory.size_limits();
+        if min > 0 {
+            problem.add_constraint(expr.as_slice(), ComparisonOp::Ge, min as f64);
+        }
+        if let Some(max) = max {
+            problem.add_constraint(expr.as_slice(), ComparisonOp::Le, max as f64);
+        }
+    }
+
+    // students with a banned pairing are never in the same advisory
+    let mut indices: HashMap<Arc<str>, Vec<usize>> = HashMap::new();
+    for (index, s) in students.iter().enumerate() {
+        indices.entry(s.name.clone()).or_default().push(index);
+    }
+    for (index, s) in students.iter().enumerate() {
+        for banned in s.banned_pairings.iter() {
+            for &other in indices.get(banned).into_iter().flatten() {
+                if other == index {
+                    continue;
+                }
+                for pair in assignments[index].iter().zip(assignments[other].iter()) {
+                    if let (Some(a), Some(b)) = pair {
+                        problem.add_constraint([(*a, 1.0), (*b, 1.0)], ComparisonOp::Le, 1.0);
+                    }
+                }
+            }
+        }
+    }
+
+    // the members of a group are in an advisory together or not at all
+    for group in settings.resolve_groups().iter() {
+        let members = group
+            .members
+            .iter()
+            .flat_map(|name| indices.get(name).into_iter().flatten())
+            .collect::<Vec<_>>();
+        for pair in members.windows(2) {
+            let rows = assignments[*pair[0]]
+                .iter()
+                .zip(assignments[*pair[1]].iter());
+            for (first, second) in rows {
+                let expr = match (*first, *second) {
+                    (Some(a), Some(b)) => vec![(a, 1.0), (b, -1.0)],
+                    (Some(v), None) | (None, Some(v)) => vec![(v, 1.0)],
+                    (None, None) => continue,
+                };
+                problem.add_constraint(expr.as_slice(), ComparisonOp::Eq, 0.0);
+            }
+        }
+    }
+
+    // students are rewarded for being with at least one of their preferred peers, which can only be true
+    // when they are in the advisory and one of the peers is too
+    let preferred_value = weights.preferred as f64 * students_per_advisory as f64;
+    for (index, s) in students.iter().enumerate() {
+        for (advisory_index, assignment) in assignments[index].iter().enumerate() {
+            let assignment = match assignment {
+                Some(assignment) => *assignment,
+                None => continue,
+            };
+            let mut peers = s
+                .preferred_pairings
+                .iter()
+                .flat_map(|name| indices.get(name).into_iter().flatten())
+                .filter(|&&other| other != index)
+                .filter_map(|&other| assignments[other][advisory_index])
+                .collect::<Vec<_>>();
+            // a peer may be asked for more than once, but each variable can only appear once
+            peers.sort_unstable();
+            peers.dedup();
+            if peers.is_empty() {
+                continue;
+            }
+            let honored = problem.add_var(preferred_value, (0.0, 1.0));
+            problem.add_constraint([(honored, 1.0), (assignment, -1.0)], ComparisonOp::Le, 0.0);
+            let expr = peers
+                .iter()
+                .map(|&v| (v, -1.0))
+                .chain(once((honored, 1.0)))
+                .collect::<Vec<_>>();
+            problem.add_constraint(expr.as_slice(), ComparisonOp::Le, 0.0);
+        }
+    }
+
+    // group students by the quotas they would take up in each advisory
+    let mut members: HashMap<(usize, Quota), (Vec<Variable>, i16, i32)> = HashMap::new();
+    for (advisory_index, advisory) in advisories.iter().enumerate() {
+        for (student_index, s) in students.iter().enumerate() {
+            if let Some(v) = assignments[student_index][advisory_index] {
+                for (quota, remaining, multiplier) in advisory.quotas_for(s, weights) {
+                    members
+                        .entry((advisory_index, quota))
+                        .or_insert_with(|| (Vec::new(), remaining, multiplier))
+                        .0
+                        .push(v);
+                }
+            }
+        }
+    }
+    let mut groups = members
+        .into_values()
+        .map(|(members, remaining, multiplier)| QuotaGroup {
+            members,
+            remaining,
+            multiplier,
+            value: problem.add_var(1.0, (f64::NEG_INFINITY, f64::INFINITY)),
+            segments: 0,
+        })
+        .collect::<Vec<_>>();
+    for group in groups.iter_mut() {
+        let around_quota = group.remaining.max(0) as usize + 1;
+        group.add_segments(&mut problem, around_quota);
+    }
+
+    loop {
+        if Instant::now() >= deadline {
+            return None;
+        }
+        let solution = problem.solve().ok()?;
+
+        // add segments for any group that went past the ones it has, since its value isn't exact there
+        let mut exact = true;
+        for group in groups.iter_mut() {
+            let count = group.members.iter().filter(|&&m| solution[m] > 0.5).count();
+            if count > group.segments {
+                group.add_segments(&mut problem, count);
+                exact = false;
+            }
+        }
+
+        if exact {
+            let mut filled = advisories.to_vec();
+            for (student_index, s) in students.iter().enumerate() {
+                let advisory_index = assignments[student_index]
+                    .iter()
+                    .position(|v| v.is_some_and(|v| solution[v] > 0.5))?;
+                filled[advisory_index].add_student(s.clone());
+            }
+            return Some((filled, solution.objective().round() as i32));
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use crate::people::Student;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Kind of values that a student field used by a [`Criterion`] holds
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldKind {
+    /// Values are labels, such as `"athlete"` or `"boarding"`, and students match when their labels are equal
+    #[default]
+    Categorical,
+    /// Values are numbers, such as a level of support needed, and are compared by size
+    Numeric,
+}
+
+/// How a [`Criterion`] wants the values of its field arranged across advisories
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum BalanceRule {
+    /// Spread students evenly, so no advisory has many students with the same value, or a high total for
+    /// numeric fields
+    Spread,
+    /// Keep students with the same value, or similar values for numeric fields, in the same advisories
+    Cluster,
+    /// Keep each advisory under a limit on students with the same value, or on the total for numeric fields
+    AtMost {
+        /// Most students with the same value, or highest total, that an advisory should have
+        max: u16,
+    },
+}
+
+/// User defined rule for balancing one of the students' fields across advisories
+///
+/// The field is read from each student's [`crate::people::Attributes`], and students without a value for it
+/// are left out of the criterion
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Criterion {
+    /// Name of the student attribute that the criterion reads
+    pub field: Arc<str>,
+    /// Kind of values that the field holds
+    #[serde(default)]
+    pub kind: FieldKind,
+    /// How the field's values should be arranged
+    #[serde(flatten)]
+    pub rule: BalanceRule,
+    /// The relative importance of the criterion
+    ///
+    /// Value from 1-10
+    pub weight: i8,
+}
+
+/// Value of a criterion's field for a student
+enum FieldValue<'a> {
+    /// Label of a categorical field
+    Label(&'a str),
+    /// Number of a numeric field
+    Number(f64),
+}
+
+impl crate::Verify for Criterion {
+    /// Finds a missing field name and a weight that is out of range
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use advisory_backend_lib::{Verify, advisories::{BalanceRule, Criterion, FieldKind}};
+    /// # use std::sync::Arc;
+    /// let athletes = Criterion {
+    ///     field: Arc::from("athlete"),
+    ///     kind: FieldKind::Categorical,
+    ///     rule: BalanceRule::AtMost { max: 3 },
+    ///     weight: 5,
+    /// };
+    /// assert!(athletes.verify().is_ok());
+    ///
+    /// let unnamed = Criterion {
+    ///     field: Arc::from(""),
+    ///     ..athletes
+    /// };
+    /// assert!(unnamed.verify().is_err());
+    /// ```
+    fn problems(&self) -> Vec<crate::Problem> {
+        let mut problems = Vec::new();
+        if self.field.is_empty() {
+            problems.push(crate::Problem::new("field", "must not be empty"));
+        }
+        if !(1..=10).contains(&self.weight) {
+            problems.push(crate::Problem::new("weight", "must be from 1-10"));
+        }
+        problems
+    }
+}
+
+impl Criterion {
+    /// Read the criterion's field from a student, or none if the student has no value for it or a numeric
+    /// field's value isn't a number
+    fn value_of<'a>(&self, s: &'a Student) -> Option<FieldValue<'a>> {
+        let value = s.attributes.get(&self.field)?;
+        match self.kind {
+            FieldKind::Categorical => Some(FieldValue::Label(value)),
+            FieldKind::Numeric => value.trim().parse().ok().map(FieldValue::Number),
+        }
+    }
+
+    /// Checks whether a student's value for the field can be read, which is any value for categorical
+    /// fields and only numbers for numeric fields
+    pub(crate) fn accepts(&self, s: &Student) -> bool {
+        !s.attributes.contains_key(&self.field) || self.value_of(s).is_some()
+    }
+
+    /// Calculate the weighted value of placing a student with the students already in an advisory
+    ///
+    /// Spreading labels subtracts the share of students with the same label and clustering them adds it.
+    /// Limits subtract how far the advisory would go over, spreading numbers subtracts the value times the
+    /// advisory's total per student it should hold, and clustering numbers subtracts the average difference.
+    /// Terms for labels and limits are scaled by the number of students per advisory, so they stay on the
+    /// same scale as the other terms
+    pub(crate) fn calculate_score(
+        &self,
+        others: &[Student],
+        student: &Student,
+        students_per_advisory: u16,
+    ) -> i32 {
+        let value = match self.value_of(student) {
+            Some(value) => value,
+            None => return 0,
+        };
+        let weight = self.weight as f64;
+        let scale = students_per_advisory.max(1) as f64;
+        let others = others.iter().filter_map(|s| self.value_of(s));
+
+        let weighted_value = match (value, self.rule) {
+            (FieldValue::Label(label), rule) => {
+                let (matching, with_value) =
+                    others.fold((0.0, 0.0), |(matching, count), other| match other {
+                        FieldValue::Label(l) if l == label => (matching + 1.0, count + 1.0),
+                        _ => (matching, count + 1.0),
+                    });
+                // the share of students with the same value, so neither rule outgrows an advisory
+                let share = match with_value > 0.0 {
+                    true => matching / with_value,
+                    false => 0.0,
+                };
+                match rule {
+                    BalanceRule::Spread => -weight * scale * share,
+                    BalanceRule::Cluster => weight * scale * share,
+                    BalanceRule::AtMost { max } => {
+                        -weight * scale * (matching + 1.0 - max as f64).max(0.0)
+                    }
+                }
+            }
+            (FieldValue::Number(number), rule) => {
+                let numbers: Vec<f64> = others
+                    .filter_map(|other| match other {
+                        FieldValue::Number(n) => Some(n),
+                        FieldValue::Label(_) => None,
+                    })
+                    .collect();
+                match rule {
+                    BalanceRule::Spread => -weight * number * numbers.iter().sum::<f64>() / scale,
+                    // the average difference, so clustering never outgrows an advisory
+                    BalanceRule::Cluster if !numbers.is_empty() => {
+                        let differences = numbers.iter().map(|n| (n - number).abs());
+                        -weight * differences.sum::<f64>() / numbers.len() as f64
+                    }
+                    BalanceRule::Cluster => 0.0,
+                    BalanceRule::AtMost { max } => {
+                        let total = numbers.iter().sum::<f64>() + number;
+                        -weight * scale * (total - max as f64).max(0.0)
+                    }
+                }
+            }
+        };
+        weighted_value.round() as i32
+    }
+}
@@ -0,0 +1,56 @@
+use crate::advisories::{Move, Organization, Score, Weights};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Organization to compare, either saved in the database or sent with the request
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum OrganizationSource {
+    /// Organization saved in the database
+    Saved {
+        /// Id of the saved organization
+        id: Arc<str>,
+    },
+    /// Organization sent with the request
+    Submitted(Organization),
+}
+
+/// Form for comparing two organizations
+#[derive(Deserialize, Serialize)]
+pub struct DiffForm {
+    /// Organization to compare from
+    pub before: OrganizationSource,
+    /// Organization to compare to
+    pub after: OrganizationSource,
+    /// Weights to score submitted organizations with
+    ///
+    /// Saved organizations are always scored with the weights they were saved with
+    #[serde(default)]
+    pub weights: Weights,
+}
+
+/// Differences between two organizations
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct OrganizationDiff {
+    /// Students that are in a different advisory afterwards
+    pub moved: Arc<[Move]>,
+    /// Students that are only in the later organization
+    pub added: Arc<[Arc<str>]>,
+    /// Students that are only in the earlier organization
+    pub removed: Arc<[Arc<str>]>,
+    /// Advisories whose advisors changed
+    pub advisors: Arc<[AdvisorChange]>,
+    /// Change in each term of the total score, from the earlier organization to the later one
+    pub score: Score,
+}
+
+/// Advisory whose advisors are different between two organizations
+///
+/// Advisories that are only in one of the organizations have no advisors on the other side
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct AdvisorChange {
+    /// Names of the advisors of the advisory before
+    pub before: Arc<[Arc<str>]>,
+    /// Names of the advisors of the advisory after
+    pub after: Arc<[Arc<str>]>,
+}
@@ -0,0 +1,87 @@
+use crate::advisories::{Organization, OrganizationScore};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Change made by hand to a saved organization
+///
+/// Advisories are referred to by their position in the organization
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Edit {
+    /// Move a student into another advisory
+    MoveStudent {
+        /// Name of the student to move
+        student: Arc<str>,
+        /// Position of the advisory to move the student into
+        to: usize,
+    },
+    /// Swap the advisories of two students
+    SwapStudents {
+        /// Name of one of the students to swap
+        first: Arc<str>,
+        /// Name of the other student to swap
+        second: Arc<str>,
+    },
+    /// Move an advisor into another advisory
+    MoveAdvisor {
+        /// Name of the advisor to move
+        advisor: Arc<str>,
+        /// Position of the advisory to move the advisor into
+        to: usize,
+    },
+}
+
+/// Problem with an organization that generation would have avoided, but a manual edit may cause
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Violation {
+    /// Student placed with other students they are banned from being with
+    BannedPairing {
+        /// Position of the advisory the student is in
+        advisory: usize,
+        /// Name of the student
+        student: Arc<str>,
+        /// Names of the students in the advisory that the student is banned from being with
+        with: Arc<[Arc<str>]>,
+    },
+    /// Student placed in an advisory with an advisor who is not supposed to advise them
+    BannedAdvisor {
+        /// Position of the advisory the student is in
+        advisory: usize,
+        /// Name of the student
+        student: Arc<str>,
+        /// Names of the advisors of the advisory that are banned from advising the student
+        advisors: Arc<[Arc<str>]>,
+    },
+    /// Advisory with more students than its size limit
+    OverCapacity {
+        /// Position of the advisory
+        advisory: usize,
+        /// Number of students in the advisory
+        students: usize,
+        /// Most students the advisory may hold
+        max: u16,
+    },
+    /// Advisory with fewer students than its minimum size
+    UnderCapacity {
+        /// Position of the advisory
+        advisory: usize,
+        /// Number of students in the advisory
+        students: usize,
+        /// Fewest students the advisory may have
+        min: u16,
+    },
+}
+
+/// Organization after a manual edit, along with its recalculated score and any violations
+#[derive(Deserialize, Serialize)]
+pub struct EditResult {
+    /// The edited organization
+    pub organization: Organization,
+    /// Score of the edited organization
+    pub score: OrganizationScore,
+    /// Every violation in the edited organization
+    pub violations: Arc<[Violation]>,
+    /// Violations that the edit caused, which weren't in the organization before
+    pub introduced: Arc<[Violation]>,
+}
@@ -0,0 +1,266 @@
+use crate::people::{Student, Teacher};
+use serde::{Deserialize, Serialize};
+use std::{cmp::Reverse, collections::HashSet, sync::Arc};
+
+/// Most passes over every pair of advisors that [`TeacherGrouping::form`] makes while improving its groupings
+const MAX_FORM_PASSES: usize = 100;
+
+/// Advisors that share an advisory, along with limits on how many students it can hold
+///
+/// Can be sent as just a list of teachers when the advisory has no limits
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(from = "GroupingForm")]
+pub struct TeacherGrouping {
+    /// Advisors of the advisory
+    pub teachers: Arc<[Teacher]>,
+    /// Fewest students that the advisory may have
+    pub min_students: Option<u16>,
+    /// Most students that the advisory may have, such as the size of its room
+    pub max_students: Option<u16>,
+}
+
+/// Forms that a [`TeacherGrouping`] can be sent in
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum GroupingForm {
+    /// Just the advisors, with no limits on the advisory's size
+    Teachers(Arc<[Teacher]>),
+    /// Advisors along with limits on the advisory's size
+    Grouping {
+        /// Advisors of the advisory
+        teachers: Arc<[Teacher]>,
+        /// Fewest students that the advisory may have
+        #[serde(default)]
+        min_students: Option<u16>,
+        /// Most students that the advisory may have
+        #[serde(default)]
+        max_students: Option<u16>,
+    },
+}
+
+impl From<GroupingForm> for TeacherGrouping {
+    fn from(value: GroupingForm) -> Self {
+        match value {
+            GroupingForm::Teachers(teachers) => Self::new(teachers),
+            GroupingForm::Grouping {
+                teachers,
+                min_students,
+                max_students,
+            } => Self {
+                teachers,
+                min_students,
+                max_students,
+            },
+        }
+    }
+}
+
+impl TeacherGrouping {
+    /// Creates a grouping of advisors with no limits on the advisory's size
+    pub fn new<T: Into<Arc<[Teacher]>>>(teachers: T) -> Self {
+        Self {
+            teachers: teachers.into(),
+            min_students: None,
+            max_students: None,
+        }
+    }
+
+    /// Group teachers into `count` advisories, maximizing how many students can be placed with an advisor
+    /// who teaches them
+    ///
+    /// Every teacher advises one advisory, with the advisors split as evenly as possible, and teachers
+    /// listed together in `bans` are never grouped together. Each advisory can only hold its share of the
+    /// students, so pairing teachers whose students overlap, or whose combined students wouldn't fit,
+    /// reaches fewer students than spreading them out.
+    ///
+    /// Teachers are placed one at a time, most banned first, so with many bans this can fail to find a
+    /// grouping even though one exists. Fails with [`crate::Error::Invalid`] if there are fewer teachers than
+    /// advisories or no grouping that keeps banned teachers apart was found
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use advisory_backend_lib::{advisories::TeacherGrouping, people::{Student, Teacher}};
+    /// # use std::sync::Arc;
+    /// let teachers: Vec<Teacher> = (0..4).map(|i| Teacher::new(format!("Teacher {}", i))).collect();
+    /// // teachers 0 and 1 teach the same two students, as do teachers 2 and 3
+    /// let students: Vec<Student> = (0..8)
+    ///     .map(|i| Student {
+    ///         name: Arc::from(format!("Student {}", i)),
+    ///         teachers: match i {
+    ///             0 | 1 => Arc::from([teachers[0].clone(), teachers[1].clone()]),
+    ///             2 | 3 => Arc::from([teachers[2].clone(), teachers[3].clone()]),
+    ///             _ => Arc::from([Teacher::new("Teacher 4")]),
+    ///         },
+    ///         ..Default::default()
+    ///     })
+    ///     .collect();
+    /// let groupings = TeacherGrouping::form(&teachers, &students, 2, &[])?;
+    /// assert!(groupings.iter().all(|g| g.teachers.len() == 2));
+    /// assert!(!groupings.iter().any(|g| g.teachers[..] == teachers[..2]));
+    ///
+    /// let bans = [(Arc::from("Teacher 0"), Arc::from("Teacher 2"))];
+    /// let groupings = TeacherGrouping::form(&teachers, &students, 2, &bans)?;
+    /// assert!(groupings.iter().all(|g| g.teachers[..] != [teachers[0].clone(), teachers[2].clone()]));
+    /// # Ok::<(), advisory_backend_lib::Error>(())
+    /// ```
+    pub fn form(
+        teachers: &[Teacher],
+        students: &[Student],
+        count: u16,
+        bans: &[(Arc<str>, Arc<str>)],
+    ) -> Result<Arc<[Self]>, crate::Error> {
+        let count = count as usize;
+        if count == 0 || teachers.len() < count {
+            let message = format!(
+                "{} teachers can't advise {} advisories",
+                teachers.len(),
+                count
+            );
+            return Err(crate::Error::Invalid(Arc::from([crate::Problem::new(
+                "num_advisories",
+                message,
+            )])));
+        }
+
+        // indices of the students that each teacher teaches
+        let taught: Vec<HashSet<usize>> = teachers
+            .iter()
+            .map(|t| {
+                students
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, s)| s.teachers.contains(t))
+                    .map(|(index, _)| index)
+                    .collect()
+            })
+            .collect();
+        let banned = |a: usize, b: usize| {
+            let (a, b) = (&teachers[a].name, &teachers[b].name);
+            bans.iter()
+                .any(|(x, y)| (x == a && y == b) || (x == b && y == a))
+        };
+        let capacity = students.len().div_ceil(count);
+        // students that can be placed with an advisor who teaches them, up to the advisory's share
+        let reach = |members: &[usize]| {
+            let reached: HashSet<usize> = members
+                .iter()
+                .flat_map(|&m| taught[m].iter().copied())
+                .collect();
+            reached.len().min(capacity)
+        };
+        let sizes: Vec<usize> = (0..count)
+            .map(|index| teachers.len() / count + (index < teachers.len() % count) as usize)
+            .collect();
+
+        // teachers with the most bans are the hardest to group, so they are grouped first
+        let mut order: Vec<usize> = (0..teachers.len()).collect();
+        order.sort_by_key(|&t| {
+            let ban_count = (0..teachers.len())
+                .filter(|&o| o != t && banned(t, o))
+                .count();
+            (Reverse(ban_count), Reverse(taught[t].len()))
+        });
+        let mut groups: Vec<Vec<usize>> = vec![Vec::new(); count];
+        for t in order {
+            let best = (0..count)
+                .filter(|&g| groups[g].len() < sizes[g] && !groups[g].iter().any(|&o| banned(t, o)))
+                .max_by_key(|&g| {
+                    let mut with = groups[g].clone();
+                    with.push(t);
+                    (
+                        reach(&with) - reach(&groups[g]),
+                        Reverse(groups[g].len()),
+                        Reverse(g),
+                    )
+                })
+                .ok_or_else(|| {
+                    let problem = crate::Problem::new(
+                        "teacher_groupings",
+                        "no grouping keeping the teacher from their banned co-advisors was found",
+                    );
+                    crate::Error::Invalid(Arc::from([problem.named(&teachers[t].name)]))
+                })?;
+            groups[best].push(t);
+        }
+
+        // swap advisors between advisories while it lets more students be placed with one of their teachers
+        for _ in 0..MAX_FORM_PASSES {
+            let mut improved = false;
+            for a in 0..count {
+                for b in a + 1..count {
+                    for i in 0..groups[a].len() {
+                        for j in 0..groups[b].len() {
+                            let (first, second) = (groups[a][i], groups[b][j]);
+                            let allowed =
+                                groups[a].iter().all(|&o| o == first || !banned(second, o))
+                                    && groups[b].iter().all(|&o| o == second || !banned(first, o));
+                            if !allowed {
+                                continue;
+                            }
+                            let mut new_a = groups[a].clone();
+                            let mut new_b = groups[b].clone();
+                            new_a[i] = second;
+                            new_b[j] = first;
+                            if reach(&new_a) + reach(&new_b) > reach(&groups[a]) + reach(&groups[b])
+                            {
+                                groups[a] = new_a;
+                                groups[b] = new_b;
+                                improved = true;
+                            }
+                        }
+                    }
+                }
+            }
+            if !improved {
+                break;
+            }
+        }
+
+        Ok(groups
+            .into_iter()
+            .map(|mut members| {
+                members.sort_unstable();
+                Self::new(
+                    members
+                        .into_iter()
+                        .map(|m| teachers[m].clone())
+                        .collect::<Arc<[_]>>(),
+                )
+            })
+            .collect())
+    }
+}
+
+impl crate::Verify for TeacherGrouping {
+    /// Finds a minimum size larger than the maximum size, and every problem with the teachers
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use advisory_backend_lib::{Verify, advisories::TeacherGrouping, people::Teacher};
+    /// let grouping = TeacherGrouping {
+    ///     min_students: Some(20),
+    ///     max_students: Some(15),
+    ///     ..TeacherGrouping::new([Teacher::new("Teacher 1")])
+    /// };
+    /// assert!(grouping.verify().is_err());
+    /// ```
+    fn problems(&self) -> Vec<crate::Problem> {
+        let mut problems = Vec::new();
+        if let (Some(min), Some(max)) = (self.min_students, self.max_students) {
+            if min > max {
+                let message = format!("must not be more than max_students ({})", max);
+                problems.push(crate::Problem::new("min_students", message));
+            }
+        }
+        for (index, t) in self.teachers.iter().enumerate() {
+            problems.extend(
+                t.problems()
+                    .into_iter()
+                    .map(|p| p.within(&format!("teachers[{}]", index))),
+            );
+        }
+        problems
+    }
+}
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Report of the students that could not be placed without breaking a banned pairing or size limit
+///
+/// Returned instead of an [`crate::advisories::Organization`] when banned pairings and advisory size limits
+/// can't all be satisfied
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Infeasibility {
+    /// Every student that could not be placed
+    pub conflicts: Arc<[Conflict]>,
+}
+
+/// A student that couldn't be placed in any advisory
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    /// Name of the student that could not be placed
+    pub student: Arc<str>,
+    /// Names of the students and advisors that kept the student out of each advisory
+    ///
+    /// May be empty if the student was kept out because the advisories were full
+    pub banned_pairings: Arc<[Arc<str>]>,
+}
+
+impl std::fmt::Display for Infeasibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<Arc<str>> = self.conflicts.iter().map(|c| c.student.clone()).collect();
+        write!(f, "unable to place {}", names.join(", "))
+    }
+}
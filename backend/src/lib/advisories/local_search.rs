@@ -0,0 +1,156 @@
+use crate::advisories::{Advisory, Weights};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Budget for the improvement pass run after students are greedily placed
+///
+/// The pass repeatedly picks a random student and either moves them to another advisory or swaps them
+/// with a student from another advisory, keeping the change if the summed
+/// [`Advisory::calculate_total_score`] of the two advisories does not drop, no banned pairing is created and
+/// neither advisory goes past its size limits. Pinned and grouped students are never moved
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct LocalSearch {
+    /// Maximum number of moves or swaps to try
+    pub iterations: u32,
+    /// Optional wall-clock limit for the pass in milliseconds
+    ///
+    /// Results are only reproducible from a seed when the pass finishes within this limit
+    #[serde(default)]
+    pub time_limit: Option<u64>,
+}
+
+/// Most moves or swaps that one pass can try
+const MAX_ITERATIONS: u32 = 100_000;
+
+/// Longest wall-clock limit that one pass can be given, in milliseconds
+const MAX_TIME_LIMIT: u64 = 60_000;
+
+impl Default for LocalSearch {
+    fn default() -> Self {
+        Self {
+            iterations: 10_000,
+            time_limit: None,
+        }
+    }
+}
+
+impl crate::Verify for LocalSearch {
+    /// Finds an iteration count or time limit that is out of range
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use advisory_backend_lib::{Verify, advisories::LocalSearch};
+    /// assert!(LocalSearch::default().verify().is_ok());
+    /// let search = LocalSearch {
+    ///     iterations: u32::MAX,
+    ///     time_limit: Some(0),
+    /// };
+    /// assert_eq!(search.problems().len(), 2);
+    /// ```
+    fn problems(&self) -> Vec<crate::Problem> {
+        let mut problems = Vec::new();
+        if !(1..=MAX_ITERATIONS).contains(&self.iterations) {
+            let message = format!("must be from 1-{}", MAX_ITERATIONS);
+            problems.push(crate::Problem::new("iterations", message));
+        }
+        if let Some(time_limit) = self.time_limit {
+            if !(1..=MAX_TIME_LIMIT).contains(&time_limit) {
+                let message = format!("must be from 1-{}", MAX_TIME_LIMIT);
+                problems.push(crate::Problem::new("time_limit", message));
+            }
+        }
+        problems
+    }
+}
+
+impl LocalSearch {
+    /// Improve already filled advisories in place, leaving the students in `fixed` where they are
+    ///
+    /// Keeping students in the advisory that `previous` maps them to counts towards the score
+    pub(crate) fn improve<R: Rng>(
+        &self,
+        advisories: &mut [Advisory],
+        weights: &Weights,
+        students_per_advisory: u16,
+        fixed: &HashSet<Arc<str>>,
+        previous: &HashMap<Arc<str>, usize>,
+        rng: &mut R,
+    ) {
+        let advisory_count = advisories.len();
+        if advisory_count < 2 {
+            return;
+        }
+
+        let start = Instant::now();
+        let time_limit = self.time_limit.map(Duration::from_millis);
+        let score = |index: usize, a: &Advisory| {
+            a.calculate_total_score(weights, students_per_advisory)
+                .total
+                + a.calculate_total_stability(index, previous, weights, students_per_advisory)
+        };
+        let mut scores: Vec<i32> = advisories
+            .iter()
+            .enumerate()
+            .map(|(index, a)| score(index, a))
+            .collect();
+
+        for _ in 0..self.iterations {
+            if time_limit.is_some_and(|limit| start.elapsed() >= limit) {
+                log::info!("Local search stopped by time limit");
+                break;
+            }
+
+            // pick two distinct advisories, the first of which must have a student to give up
+            let from = rng.gen_range(0..advisory_count);
+            let to = (from + rng.gen_range(1..advisory_count)) % advisory_count;
+            if advisories[from].student_count() == 0 {
+                continue;
+            }
+
+            let mut new_from = advisories[from].clone();
+            let mut new_to = advisories[to].clone();
+            let student = new_from.remove_student(rng.gen_range(0..new_from.student_count()));
+            // swap half of the time, otherwise just move the student
+            let other = match new_to.student_count() > 0 && rng.gen_bool(0.5) {
+                true => Some(new_to.remove_student(rng.gen_range(0..new_to.student_count()))),
+                false => None,
+            };
+            if fixed.contains(&student.name)
+                || other.as_ref().is_some_and(|o| fixed.contains(&o.name))
+            {
+                continue;
+            }
+            // moves can't take an advisory past its size limits, while swaps keep sizes the same
+            if other.is_none() && (!advisories[from].can_spare() || !advisories[to].has_room()) {
+                continue;
+            }
+            // banned pairings are never allowed, no matter how much the score would improve
+            if new_to.has_banned_pairing(&student)
+                || other
+                    .as_ref()
+                    .is_some_and(|o| new_from.has_banned_pairing(o))
+            {
+                continue;
+            }
+            if let Some(other) = other {
+                new_from.add_student(other);
+            }
+            new_to.add_student(student);
+
+            let new_from_score = score(from, &new_from);
+            let new_to_score = score(to, &new_to);
+            if new_from_score + new_to_score >= scores[from] + scores[to] {
+                advisories[from] = new_from;
+                advisories[to] = new_to;
+                scores[from] = new_from_score;
+                scores[to] = new_to_score;
+            }
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use crate::people::Student;
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+
+/// Order that students are placed into advisories in
+///
+/// Students placed earlier get first pick of the advisories, so the order has a large effect on the result
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PlacementOrder {
+    /// Place students in the order they were passed in
+    #[default]
+    Input,
+    /// Shuffle students using the seed from [`crate::advisories::Settings`]
+    Shuffled,
+    /// Place students with the most banned pairings first, then those with the fewest teachers
+    ///
+    /// Students that are equally hard to place are shuffled using the seed
+    HardestFirst,
+}
+
+impl PlacementOrder {
+    /// Order students for placement
+    ///
+    /// Every order other than [`PlacementOrder::Input`] starts from students sorted by name, so the
+    /// result only depends on the students and the random number generator, not the order they were loaded
+    pub(crate) fn apply<R: Rng>(&self, students: &[Student], rng: &mut R) -> Vec<Student> {
+        let mut ordered = students.to_vec();
+        if *self == Self::Input {
+            return ordered;
+        }
+
+        ordered.sort_by(|a, b| a.name.cmp(&b.name));
+        ordered.shuffle(rng);
+        if *self == Self::HardestFirst {
+            // stable sort, so shuffled order is kept between students that are equally hard to place
+            ordered.sort_by_key(|s| {
+                (
+                    Reverse(s.banned_pairings.len() + s.banned_advisors.len()),
+                    s.teachers.len(),
+                )
+            });
+        }
+        ordered
+    }
+}
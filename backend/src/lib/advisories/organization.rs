@@ -1,11 +1,23 @@
 use crate::{
-    advisories::{Advisory, Settings},
-    people::{Student, Teacher},
-    Verify,
+    advisories::{
+        advisory::AttributeQuota,
+        algorithm::{solve_exact, ExactSolve},
+        saved::Placement,
+        AdvisorChange, Advisory, Algorithm, Conflict, Edit, EditResult, Infeasibility, Move,
+        OrganizationDiff, OrganizationScore, PlacementOrder, PreferenceReport, Regeneration,
+        RunScore, Settings, TeacherGrouping, Violation, Weights,
+    },
+    people::{GradeLevels, Group, Student, Teacher},
+    Error, Problem, Verify,
 };
 use axum::http::StatusCode;
+use rand::{rngs::StdRng, SeedableRng};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 /// Multiple advisories make up an organization
 /// Generating this struct is the goal of the program
@@ -25,56 +37,1183 @@ impl From<Vec<Advisory>> for Organization {
     }
 }
 
+/// The best scoring organization out of several runs of [`Organization::generate_best`]
+#[derive(Deserialize, Serialize)]
+pub struct BestOrganization {
+    /// The highest scoring organization
+    pub organization: Organization,
+    /// Score of the highest scoring organization
+    pub score: OrganizationScore,
+    /// Seed that generated the highest scoring organization
+    pub seed: u64,
+    /// Seed and total score of every run, in the order they were run
+    pub runs: Arc<[RunScore]>,
+    /// Highest total score possible, if [`Algorithm::Exact`] finished in time
+    ///
+    /// The organization is optimal when this equals its total score
+    pub bound: Option<i32>,
+    /// Groupings of advisors that were formed automatically, if none were given
+    #[serde(default)]
+    pub teacher_groupings: Option<Arc<[TeacherGrouping]>>,
+}
+
 impl Organization {
-    /// Allocate a vector of advisories of the appropriate size for the number of students and
-    /// advisories
-    fn allocate_advisories(student_count: u16, advisory_count: u16) -> Vec<Advisory> {
-        vec![Advisory::new(student_count / advisory_count); advisory_count as usize]
+    /// Score every advisory in the organization and sum them into an overall score
+    ///
+    /// Uses the same terms as placement, so the totals of two organizations built from the same students
+    /// can be compared directly
+    pub fn score(&self, weights: &Weights) -> OrganizationScore {
+        self.score_against(weights, &HashMap::new())
+    }
+
+    /// Score the organization like [`Organization::score`], also counting the stability of students kept in
+    /// the advisory that `previous` maps their name to
+    pub(crate) fn score_against(
+        &self,
+        weights: &Weights,
+        previous: &HashMap<Arc<str>, usize>,
+    ) -> OrganizationScore {
+        let student_count: usize = self.0.iter().map(|a| a.student_count()).sum();
+        let students_per_advisory = match self.0.len() {
+            0 => 0,
+            advisory_count => (student_count / advisory_count) as u16,
+        };
+
+        let advisories = self
+            .0
+            .iter()
+            .enumerate()
+            .map(|(index, a)| {
+                let mut score = a.calculate_total_score(weights, students_per_advisory);
+                score.stability =
+                    a.calculate_total_stability(index, previous, weights, students_per_advisory);
+                score.total += score.stability;
+                score
+            })
+            .collect::<Arc<[_]>>();
+        let preferences = PreferenceReport {
+            requested: self
+                .0
+                .iter()
+                .flat_map(|a| a.students())
+                .filter(|s| !s.preferred_pairings.is_empty())
+                .count(),
+            honored: self
+                .0
+                .iter()
+                .map(|a| {
+                    a.students()
+                        .iter()
+                        .filter(|s| a.has_preferred_pairing(s))
+                        .count()
+                })
+                .sum(),
+            advisors_requested: self
+                .0
+                .iter()
+                .flat_map(|a| a.students())
+                .filter(|s| !s.preferred_advisors.is_empty())
+                .count(),
+            advisors_honored: self
+                .0
+                .iter()
+                .map(|a| {
+                    a.students()
+                        .iter()
+                        .filter(|s| a.has_preferred_advisor(s))
+                        .count()
+                })
+                .sum(),
+        };
+        OrganizationScore {
+            total: advisories.iter().copied().sum(),
+            advisories,
+            preferences,
+        }
+    }
+
+    /// Allocate a vector of advisories with quotas matching the makeup of the students
+    ///
+    /// Students are split as evenly as the groupings' size limits allow, and then the students with each
+    /// grade and each value of the balanced attributes are dealt out in proportion to each advisory's size,
+    /// so each advisory's grade quotas add up to its person quota
+    fn allocate_advisories(
+        students: &[Student],
+        groupings: &[TeacherGrouping],
+        grade_levels: &GradeLevels,
+        weights: &Weights,
+    ) -> Vec<Advisory> {
+        let mut grade_counts = vec![0; grade_levels.levels.len()];
+        for s in students.iter() {
+            if let Some(index) = grade_levels.index_of(&s.grade) {
+                grade_counts[index] += 1;
+            }
+        }
+
+        let capacities = Organization::fill_capacities(students.len(), groupings);
+        let grades = Organization::deal(&grade_counts, &capacities);
+        // each attribute's values, along with the quotas dealt for each value
+        let attributes: Vec<(AttributeQuota, Vec<Vec<i16>>)> = weights
+            .balanced_attributes()
+            .into_iter()
+            .map(|(name, _)| {
+                let values: Vec<Arc<str>> = students
+                    .iter()
+                    .filter_map(|s| s.attributes.get(&name).cloned())
+                    .collect::<BTreeSet<Arc<str>>>()
+                    .into_iter()
+                    .collect();
+                let mut counts = vec![0; values.len()];
+                for value in students.iter().filter_map(|s| s.attributes.get(&name)) {
+                    if let Some(index) = values.iter().position(|v| v == value) {
+                        counts[index] += 1;
+                    }
+                }
+                let dealt = Organization::deal(&counts, &capacities);
+                let quota = AttributeQuota {
+                    name,
+                    values,
+                    remaining: Vec::new(),
+                };
+                (quota, dealt)
+            })
+            .collect();
+
+        groupings
+            .iter()
+            .enumerate()
+            .map(|(index, grouping)| {
+                Advisory::new(
+                    capacities[index] as i16,
+                    attributes
+                        .iter()
+                        .map(|(quota, dealt)| AttributeQuota {
+                            remaining: dealt.iter().map(|quotas| quotas[index]).collect(),
+                            ..quota.clone()
+                        })
+                        .collect(),
+                    grades.iter().map(|quotas| quotas[index]).collect(),
+                    grade_levels.clone(),
+                    grouping.min_students.unwrap_or(0),
+                    grouping.max_students,
+                )
+            })
+            .collect()
+    }
+
+    /// Pick how many students each advisory should have
+    ///
+    /// Every advisory starts at its minimum size, and then each student goes to the smallest advisory that
+    /// has room, so sizes are as even as the limits allow and any remainder goes to the first advisories
+    fn fill_capacities(student_count: usize, groupings: &[TeacherGrouping]) -> Vec<usize> {
+        let mut capacities: Vec<usize> = groupings
+            .iter()
+            .map(|g| g.min_students.unwrap_or(0) as usize)
+            .collect();
+        let assigned: usize = capacities.iter().sum();
+        for _ in assigned..student_count {
+            let smallest = groupings
+                .iter()
+                .enumerate()
+                .filter(|(index, g)| match g.max_students {
+                    Some(max) => capacities[*index] < max as usize,
+                    None => true,
+                })
+                .min_by_key(|(index, _)| capacities[*index])
+                .map(|(index, _)| index);
+            match smallest {
+                Some(index) => capacities[index] += 1,
+                None => break,
+            }
+        }
+        capacities
+    }
+
+    /// Deal the students of each group out in proportion to each advisory's capacity
+    ///
+    /// Groups are dealt one after another, each student going to the advisory with the largest share of its
+    /// capacity still open, so every advisory's quotas add up to no more than its capacity
+    fn deal(counts: &[usize], capacities: &[usize]) -> Vec<Vec<i16>> {
+        let mut dealt = vec![0; capacities.len()];
+        counts
+            .iter()
+            .map(|&count| {
+                let mut quotas = vec![0; capacities.len()];
+                for _ in 0..count {
+                    // compare the open share of two advisories without dividing
+                    let most_open = (0..capacities.len())
+                        .filter(|&index| dealt[index] < capacities[index])
+                        .reduce(|best, index| {
+                            let open = (capacities[index] - dealt[index]) * capacities[best];
+                            let best_open = (capacities[best] - dealt[best]) * capacities[index];
+                            match open > best_open {
+                                true => index,
+                                false => best,
+                            }
+                        });
+                    if let Some(index) = most_open {
+                        dealt[index] += 1;
+                        quotas[index] += 1;
+                    }
+                }
+                quotas
+            })
+            .collect()
     }
 
     /// Assign teachers to advisories in accordance with the groupings passed in
-    fn assign_teachers(advisories: &mut [Advisory], teacher_groupings: Arc<[Arc<[Teacher]>]>) {
+    fn assign_teachers(advisories: &mut [Advisory], teacher_groupings: &[TeacherGrouping]) {
         for (index, target_advisory) in advisories.iter_mut().enumerate() {
             teacher_groupings[index]
+                .teachers
                 .iter()
                 .for_each(|t| target_advisory.add_teacher(t.clone()));
         }
     }
 
+    /// Split students into the units that are placed together, which are the members of each group and
+    /// every other student on their own
+    ///
+    /// Units are in the order of their first student
+    fn units<'a>(students: &'a [Student], groups: &[Group]) -> Vec<Vec<&'a Student>> {
+        let mut units: Vec<Vec<&Student>> = Vec::with_capacity(students.len());
+        let mut group_units: HashMap<usize, usize> = HashMap::new();
+        for s in students.iter() {
+            match groups.iter().position(|g| g.members.contains(&s.name)) {
+                Some(group) => match group_units.get(&group) {
+                    Some(&unit) => units[unit].push(s),
+                    None => {
+                        group_units.insert(group, units.len());
+                        units.push(vec![s]);
+                    }
+                },
+                None => units.push(vec![s]),
+            }
+        }
+        units
+    }
+
+    /// Calculate the weighted value of placing a unit of students in the advisory at `index`, adding the
+    /// value of each student as if the ones before them were already placed
+    fn unit_weight(
+        advisory: &Advisory,
+        index: usize,
+        unit: &[&Student],
+        weights: &Weights,
+        students_per_advisory: u16,
+        previous: &HashMap<Arc<str>, usize>,
+    ) -> i32 {
+        let weight = |a: &Advisory, s: &Student| {
+            a.calculate_weight(s, weights, students_per_advisory)
+                + Advisory::calculate_stability_weight(
+                    index,
+                    s,
+                    previous,
+                    weights,
+                    students_per_advisory,
+                )
+        };
+        match unit {
+            [student] => weight(advisory, student),
+            _ => {
+                let mut scratch = advisory.clone();
+                unit.iter()
+                    .map(|s| {
+                        let value = weight(&scratch, s);
+                        scratch.add_student((*s).clone());
+                        value
+                    })
+                    .sum()
+            }
+        }
+    }
+
+    /// Move a single student out of the way of a student that every advisory has a banned pairing for
+    ///
+    /// Only advisories that `open` allows are used for either student, and students in `fixed` are never
+    /// moved. Returns the index of the advisory that the student can now be placed in, if any
+    fn make_room(
+        advisories: &mut [Advisory],
+        student: &Student,
+        weights: &Weights,
+        students_per_advisory: u16,
+        open: impl Fn(&Advisory) -> bool,
+        fixed: &HashSet<Arc<str>>,
+    ) -> Option<usize> {
+        for target in 0..advisories.len() {
+            // only advisories with a single student in the way can be freed up
+            let blocking = advisories[target].banned_students_with(student);
+            if !open(&advisories[target])
+                || advisories[target].has_banned_advisor(student)
+                || blocking.len() != 1
+            {
+                continue;
+            }
+
+            let blocker = advisories[target].get_student(blocking[0]);
+            if fixed.contains(&blocker.name) {
+                continue;
+            }
+            let destination: Option<usize> = advisories
+                .iter()
+                .enumerate()
+                .filter(|(index, a)| *index != target && open(a) && !a.has_banned_pairing(blocker))
+                .max_by_key(|(_, a)| a.calculate_weight(blocker, weights, students_per_advisory))
+                .map(|(index, _)| index);
+            if let Some(destination) = destination {
+                let blocker = advisories[target].remove_student(blocking[0]);
+                advisories[destination].add_student(blocker);
+                return Some(target);
+            }
+        }
+        None
+    }
+
     /// Places students into advisories and returns a vector of them
-    pub async fn generate(form: &Settings, students: Arc<[Student]>) -> Result<Self, StatusCode> {
-        log::trace!("Building advisories");
+    ///
+    /// Students pinned by [`Settings::pins`] are placed first, and the members of each of [`Settings::groups`]
+    /// are placed together as one. Everyone else is never placed with someone they have a banned pairing
+    /// with, or into an advisory that is already at its size limit; if that isn't possible for every
+    /// student, an [`Infeasibility`] report naming them is returned instead
+    ///
+    /// Given the same students, settings and seed, the same organization is always generated. Students are
+    /// placed on a blocking thread, so other requests aren't held up while they are
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use advisory_backend_lib::{advisories::{Organization, PlacementOrder, Settings, TeacherGrouping}, people::{Student, Teacher}};
+    /// # use std::sync::Arc;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), advisory_backend_lib::Error> {
+    /// let students: Arc<[Student]> = (0..20)
+    ///     .map(|i| Student {
+    ///         name: Arc::from(format!("Student {}", i)),
+    ///         teachers: Arc::from([Teacher::new(format!("Teacher {}", i % 4))]),
+    ///         ..Default::default()
+    ///     })
+    ///     .collect();
+    /// let settings = Settings {
+    ///     num_advisories: 2,
+    ///     teacher_groupings: Arc::from([
+    ///         TeacherGrouping::new([Teacher::new("Teacher 0"), Teacher::new("Teacher 1")]),
+    ///         TeacherGrouping::new([Teacher::new("Teacher 2"), Teacher::new("Teacher 3")]),
+    ///     ]),
+    ///     seed: Some(42),
+    ///     ordering: PlacementOrder::Shuffled,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let first = Organization::generate(&settings, students.clone()).await?;
+    /// let mut reversed = students.to_vec();
+    /// reversed.reverse();
+    /// let second = Organization::generate(&settings, reversed.into()).await?;
+    /// assert_eq!(
+    ///     serde_json::to_string(&first).unwrap(),
+    ///     serde_json::to_string(&second).unwrap()
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn generate(form: &Settings, students: Arc<[Student]>) -> Result<Self, Error> {
+        let form = form.clone();
+        tokio::task::spawn_blocking(move || Organization::generate_blocking(&form, students))
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    }
+
+    /// Generate advisories [`Settings::runs`] times in parallel and keep the highest scoring result
+    ///
+    /// Runs that can't place every student are skipped, and if none can, the first run's error is returned
+    ///
+    /// With [`Algorithm::Exact`], the exact solver's result is kept instead if it finishes within
+    /// [`Settings::exact_timeout`], which also lets students be placed when every greedy run fails
+    pub async fn generate_best(
+        form: &Settings,
+        students: Arc<[Student]>,
+    ) -> Result<BestOrganization, Error> {
+        Organization::generate_best_from(form, students, Arc::new(HashMap::new())).await
+    }
+
+    /// [`Organization::generate_best`], also scoring the stability of students kept in the advisory that
+    /// `previous` maps their name to
+    async fn generate_best_from(
+        form: &Settings,
+        students: Arc<[Student]>,
+        previous: Arc<HashMap<Arc<str>, usize>>,
+    ) -> Result<BestOrganization, Error> {
+        Organization::verify_inputs(form, &students)?;
+
+        let mut base = form.clone();
+        let base_seed = base.resolve_seed();
+        let handles = (0..form.runs as u64)
+            .map(|run| {
+                let mut settings = base.clone();
+                settings.seed = Some(base_seed.wrapping_add(run));
+                if run > 0 && settings.ordering == PlacementOrder::Input {
+                    settings.ordering = PlacementOrder::Shuffled;
+                }
+                let students = students.clone();
+                let previous = previous.clone();
+                tokio::task::spawn_blocking(move || {
+                    Organization::place(&settings, students, &previous).map(|organization| {
+                        let score = organization.score_against(&settings.weights, &previous);
+                        (organization, score)
+                    })
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut runs: Vec<RunScore> = Vec::with_capacity(handles.len());
+        let mut best: Option<(Organization, OrganizationScore, u64)> = None;
+        let mut first_error: Option<Error> = None;
+        for (run, handle) in handles.into_iter().enumerate() {
+            let seed = base_seed.wrapping_add(run as u64);
+            let result = handle
+                .await
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            match result {
+                Ok((organization, score)) => {
+                    runs.push(RunScore {
+                        seed,
+                        total: Some(score.total.total),
+                    });
+                    // earlier runs win ties
+                    let better = match &best {
+                        Some((_, best_score, _)) => score.total.total > best_score.total.total,
+                        None => true,
+                    };
+                    if better {
+                        best = Some((organization, score, seed));
+                    }
+                }
+                Err(error) => {
+                    runs.push(RunScore { seed, total: None });
+                    first_error.get_or_insert(error);
+                }
+            }
+        }
+
+        let mut bound: Option<i32> = None;
+        if form.algorithm == Algorithm::Exact {
+            if let Some((organization, exact_bound)) =
+                Organization::generate_exact(form, students, previous.clone()).await
+            {
+                let score = organization.score_against(&form.weights, &previous);
+                bound = Some(exact_bound);
+                let better = match &best {
+                    Some((_, best_score, _)) => score.total.total >= best_score.total.total,
+                    None => true,
+                };
+                if better {
+                    best = Some((organization, score, base_seed));
+                }
+            }
+        }
+
+        match (best, first_error) {
+            (Some((organization, score, seed)), _) => Ok(BestOrganization {
+                organization,
+                score,
+                seed,
+                runs: runs.into(),
+                bound,
+                teacher_groupings: None,
+            }),
+            (None, Some(error)) => Err(error),
+            (None, None) => Err(StatusCode::INTERNAL_SERVER_ERROR.into()),
+        }
+    }
+
+    /// Generate advisories like [`Organization::generate_best`] while keeping students in the advisory they
+    /// were in within `previous` where possible
+    ///
+    /// Each previous advisory is matched to a different current grouping, preferring one with exactly the
+    /// same advisors over one that only shares an advisor. Keeping a student in
+    /// their previous advisory is worth [`Weights::stability`] in the same way that having an advisor as a
+    /// teacher is worth [`Weights::has_teacher`], so higher stability means fewer students moved. Every
+    /// student that moved, was added, or was removed is reported
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use advisory_backend_lib::{advisories::{Organization, Settings, TeacherGrouping}, people::{Student, Teacher}};
+    /// # use std::sync::Arc;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), advisory_backend_lib::Error> {
+    /// let students: Arc<[Student]> = (0..20)
+    ///     .map(|i| Student {
+    ///         name: Arc::from(format!("Student {}", i)),
+    ///         ..Default::default()
+    ///     })
+    ///     .collect();
+    /// let settings = Settings {
+    ///     num_advisories: 2,
+    ///     teacher_groupings: Arc::from([
+    ///         TeacherGrouping::new([Teacher::new("Teacher 0")]),
+    ///         TeacherGrouping::new([Teacher::new("Teacher 1")]),
+    ///     ]),
+    ///     seed: Some(42),
+    ///     ..Default::default()
+    /// };
+    /// let previous = Organization::generate(&settings, students[..18].into()).await?;
+    ///
+    /// let regenerated = Organization::regenerate(&settings, &previous, students).await?;
+    /// assert!(regenerated.moved.is_empty());
+    /// assert_eq!(regenerated.added.len(), 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn regenerate(
+        form: &Settings,
+        previous: &Organization,
+        students: Arc<[Student]>,
+    ) -> Result<Regeneration, Error> {
+        // match each previous advisory to a different current grouping that shares its advisors
+        let grouping_sets: Vec<HashSet<Arc<str>>> = form
+            .teacher_groupings
+            .iter()
+            .map(|g| g.teachers.iter().map(|t| t.name.clone()).collect())
+            .collect();
+        let previous_sets: Vec<HashSet<Arc<str>>> = previous
+            .0
+            .iter()
+            .map(|a| a.advisors().iter().map(|t| t.name.clone()).collect())
+            .collect();
+        let matches = match_advisor_sets(&grouping_sets, &previous_sets, false);
+        let previous_advisories = previous.advisory_indices();
+        let placements: HashMap<Arc<str>, usize> = previous_advisories
+            .iter()
+            .filter_map(|(name, &index)| matches[index].map(|matched| (name.clone(), matched)))
+            .collect();
+
+        let generated =
+            Organization::generate_best_from(form, students.clone(), Arc::new(placements)).await?;
+
+        let moved = previous.moved_students(&generated.organization, |before, after| {
+            matches[before] == Some(after)
+        });
+        let added = students
+            .iter()
+            .filter(|s| !previous_advisories.contains_key(&s.name))
+            .map(|s| s.name.clone())
+            .collect();
+        let removed = previous
+            .0
+            .iter()
+            .flat_map(|a| a.students())
+            .filter(|p| !students.iter().any(|s| s.name == p.name))
+            .map(|p| p.name.clone())
+            .collect();
+
+        Ok(Regeneration {
+            generated,
+            moved,
+            added,
+            removed,
+        })
+    }
+
+    /// Names of the advisors and students of each advisory, which is how saved organizations are stored
+    pub(crate) fn placements(&self) -> Vec<Placement> {
+        self.0
+            .iter()
+            .map(|a| {
+                let advisors = a.advisors().iter().map(|t| t.name.clone()).collect();
+                let students = a.students().iter().map(|s| s.name.clone()).collect();
+                (advisors, students)
+            })
+            .collect()
+    }
+
+    /// Rebuild a saved organization from the names of the advisors and students in each advisory
+    ///
+    /// Quotas are sized from the settings it was generated with, falling back to advisories without size
+    /// limits if the settings no longer have a grouping for each advisory. Students are looked up among
+    /// `students`, and anyone that isn't there is kept with only their name
+    pub(crate) fn rebuild(form: &Settings, placements: &[Placement], students: &[Student]) -> Self {
+        let groupings: Vec<TeacherGrouping> = match form.teacher_groupings.len() == placements.len()
+        {
+            true => form.teacher_groupings.to_vec(),
+            false => placements
+                .iter()
+                .map(|_| TeacherGrouping::new(Vec::new()))
+                .collect(),
+        };
+        let placed: Vec<Student> = placements
+            .iter()
+            .flat_map(|(_, names)| names.iter())
+            .map(|name| match students.iter().find(|s| &s.name == name) {
+                Some(student) => student.clone(),
+                None => Student {
+                    name: name.clone(),
+                    ..Default::default()
+                },
+            })
+            .collect();
+
+        let mut advisories = Organization::allocate_advisories(
+            &placed,
+            &groupings,
+            &form.resolve_grade_levels(),
+            &form.weights,
+        );
+        let mut placed = placed.into_iter();
+        for (advisory, (advisors, names)) in advisories.iter_mut().zip(placements) {
+            advisors
+                .iter()
+                .for_each(|name| advisory.add_teacher(Teacher::new(name.clone())));
+            placed
+                .by_ref()
+                .take(names.len())
+                .for_each(|s| advisory.add_student(s));
+        }
+        advisories.into()
+    }
+
+    /// Apply a manual edit, returning the edited organization along with its recalculated score
+    ///
+    /// Edits are made even if they break a banned pairing or size limit, so that placements can be
+    /// overridden by hand, and any violations they cause are reported instead. Fails with
+    /// [`crate::Error::Invalid`] if a named person or advisory position isn't in the organization
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use advisory_backend_lib::{advisories::{Edit, Organization, Settings, TeacherGrouping, Violation}, people::{Student, Teacher}};
+    /// # use std::sync::Arc;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), advisory_backend_lib::Error> {
+    /// let mut students: Vec<Student> = (0..10)
+    ///     .map(|i| Student {
+    ///         name: Arc::from(format!("Student {}", i)),
+    ///         ..Default::default()
+    ///     })
+    ///     .collect();
+    /// students[0].banned_advisors = Arc::from([Arc::from("Teacher 1")]);
+    /// let settings = Settings {
+    ///     num_advisories: 2,
+    ///     teacher_groupings: Arc::from([
+    ///         TeacherGrouping::new([Teacher::new("Teacher 0")]),
+    ///         TeacherGrouping::new([Teacher::new("Teacher 1")]),
+    ///     ]),
+    ///     ..Default::default()
+    /// };
+    /// let organization = Organization::generate(&settings, students.into()).await?;
+    ///
+    /// let edit = Edit::MoveAdvisor { advisor: Arc::from("Teacher 1"), to: 0 };
+    /// let edited = organization.edit(&edit, &settings.weights)?;
+    /// assert!(matches!(edited.introduced[..], [Violation::BannedAdvisor { .. }]));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn edit(&self, edit: &Edit, weights: &Weights) -> Result<EditResult, Error> {
+        let mut advisories = self.0.to_vec();
+        // find the advisory a student is in along with their position in it
+        let find_student = |advisories: &[Advisory], field: &str, name: &str| {
+            advisories
+                .iter()
+                .enumerate()
+                .find_map(|(index, a)| {
+                    a.students()
+                        .iter()
+                        .position(|s| &*s.name == name)
+                        .map(|position| (index, position))
+                })
+                .ok_or_else(|| {
+                    let message = format!("{} isn't in any advisory", name);
+                    Error::Invalid(Arc::new([Problem::new(field, message)]))
+                })
+        };
+        let count = advisories.len();
+        let out_of_range = |to: usize| {
+            let message = format!("advisory {} doesn't exist, there are only {}", to, count);
+            Error::Invalid(Arc::new([Problem::new("to", message)]))
+        };
+
+        match edit {
+            Edit::MoveStudent { student, to } => {
+                if *to >= advisories.len() {
+                    return Err(out_of_range(*to));
+                }
+                let (from, position) = find_student(&advisories, "student", student)?;
+                let student = advisories[from].remove_student(position);
+                advisories[*to].add_student(student);
+            }
+            Edit::SwapStudents { first, second } => {
+                let (first_advisory, first_position) = find_student(&advisories, "first", first)?;
+                let (second_advisory, second_position) =
+                    find_student(&advisories, "second", second)?;
+                if first_advisory != second_advisory {
+                    let first = advisories[first_advisory].remove_student(first_position);
+                    let second = advisories[second_advisory].remove_student(second_position);
+                    advisories[first_advisory].add_student(second);
+                    advisories[second_advisory].add_student(first);
+                }
+            }
+            Edit::MoveAdvisor { advisor, to } => {
+                if *to >= advisories.len() {
+                    return Err(out_of_range(*to));
+                }
+                let teacher = advisories
+                    .iter_mut()
+                    .find_map(|a| a.remove_teacher(advisor))
+                    .ok_or_else(|| {
+                        let message = format!("{} isn't advising any advisory", advisor);
+                        Error::Invalid(Arc::new([Problem::new("advisor", message)]))
+                    })?;
+                advisories[*to].add_teacher(teacher);
+            }
+        }
+
+        let before = self.violations();
+        let organization: Organization = advisories.into();
+        let violations = organization.violations();
+        let introduced = violations
+            .iter()
+            .filter(|v| !before.contains(v))
+            .cloned()
+            .collect();
+        Ok(EditResult {
+            score: organization.score(weights),
+            organization,
+            violations,
+            introduced,
+        })
+    }
+
+    /// Find every banned pairing, banned advisor and size limit that the organization breaks
+    pub fn violations(&self) -> Arc<[Violation]> {
+        let mut violations: Vec<Violation> = Vec::new();
+        for (index, advisory) in self.0.iter().enumerate() {
+            for s in advisory.students() {
+                let banned = advisory.banned_student_names(s);
+                if !banned.is_empty() {
+                    violations.push(Violation::BannedPairing {
+                        advisory: index,
+                        student: s.name.clone(),
+                        with: banned.into(),
+                    });
+                }
+                let advisors = advisory.banned_advisor_names(s);
+                if !advisors.is_empty() {
+                    violations.push(Violation::BannedAdvisor {
+                        advisory: index,
+                        student: s.name.clone(),
+                        advisors: advisors.into(),
+                    });
+                }
+            }
+
+            let students = advisory.student_count();
+            match advisory.size_limits() {
+                (_, Some(max)) if students > max as usize => {
+                    violations.push(Violation::OverCapacity {
+                        advisory: index,
+                        students,
+                        max,
+                    })
+                }
+                (min, _) if students < min as usize => violations.push(Violation::UnderCapacity {
+                    advisory: index,
+                    students,
+                    min,
+                }),
+                _ => {}
+            }
+        }
+        violations.into()
+    }
+
+    /// Compare the organization to a later one, listing the students that moved, the advisories whose
+    /// advisors changed, and the change in each score term
+    ///
+    /// Each organization is scored with its own weights, such as the ones it was saved with. Pass the same
+    /// weights for both so the change in score only reflects the placements
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use advisory_backend_lib::{advisories::{Edit, Organization, Settings, TeacherGrouping}, people::{Student, Teacher}};
+    /// # use std::sync::Arc;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), advisory_backend_lib::Error> {
+    /// let students: Arc<[Student]> = (0..10)
+    ///     .map(|i| Student {
+    ///         name: Arc::from(format!("Student {}", i)),
+    ///         ..Default::default()
+    ///     })
+    ///     .collect();
+    /// let settings = Settings {
+    ///     num_advisories: 2,
+    ///     teacher_groupings: Arc::from([
+    ///         TeacherGrouping::new([Teacher::new("Teacher 0")]),
+    ///         TeacherGrouping::new([Teacher::new("Teacher 1")]),
+    ///     ]),
+    ///     ..Default::default()
+    /// };
+    /// let before = Organization::generate(&settings, students).await?;
+    /// let edit = Edit::MoveAdvisor { advisor: Arc::from("Teacher 1"), to: 0 };
+    /// let after = before.edit(&edit, &settings.weights)?.organization;
+    ///
+    /// let diff = before.diff(&after, &settings.weights, &settings.weights);
+    /// assert_eq!(diff.moved.len(), 0);
+    /// assert_eq!(diff.advisors.len(), 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn diff(
+        &self,
+        after: &Organization,
+        before_weights: &Weights,
+        after_weights: &Weights,
+    ) -> OrganizationDiff {
+        let matches = Organization::match_advisories(&self.0, &after.0);
+        let advisor_names = |a: &Advisory| -> Arc<[Arc<str>]> {
+            a.advisors().iter().map(|t| t.name.clone()).collect()
+        };
+        let before_placements = self.advisory_indices();
+        let after_placements = after.advisory_indices();
+
+        let moved = self.moved_students(after, |before, after| matches[after] == Some(before));
+        let only_in = |organization: &Organization, other: &HashMap<Arc<str>, usize>| {
+            organization
+                .0
+                .iter()
+                .flat_map(|a| a.students())
+                .filter(|s| !other.contains_key(&s.name))
+                .map(|s| s.name.clone())
+                .collect::<Arc<[_]>>()
+        };
+
+        let mut advisors: Vec<AdvisorChange> = Vec::new();
+        for (index, advisory) in after.0.iter().enumerate() {
+            let before: Arc<[Arc<str>]> = match matches[index] {
+                Some(before_index) => advisor_names(&self.0[before_index]),
+                None => Arc::from([]),
+            };
+            let after = advisor_names(advisory);
+            let mut sorted = (before.to_vec(), after.to_vec());
+            sorted.0.sort();
+            sorted.1.sort();
+            if sorted.0 != sorted.1 {
+                advisors.push(AdvisorChange { before, after });
+            }
+        }
+        for (index, advisory) in self.0.iter().enumerate() {
+            if !matches.contains(&Some(index)) {
+                advisors.push(AdvisorChange {
+                    before: advisor_names(advisory),
+                    after: Arc::from([]),
+                });
+            }
+        }
+
+        OrganizationDiff {
+            moved,
+            added: only_in(after, &before_placements),
+            removed: only_in(self, &after_placements),
+            advisors: advisors.into(),
+            score: after.score(after_weights).total - self.score(before_weights).total,
+        }
+    }
+
+    /// Index of the advisory that each student is in, by the student's name
+    fn advisory_indices(&self) -> HashMap<Arc<str>, usize> {
+        self.0
+            .iter()
+            .enumerate()
+            .flat_map(|(index, a)| a.students().iter().map(move |s| (s.name.clone(), index)))
+            .collect()
+    }
+
+    /// Every student in both organizations whose advisory in `after` isn't the one that `same` matches to
+    /// their advisory in this organization, given the index of each
+    fn moved_students(
+        &self,
+        after: &Organization,
+        same: impl Fn(usize, usize) -> bool,
+    ) -> Arc<[Move]> {
+        let before_indices = self.advisory_indices();
+        let advisor_names = |a: &Advisory| -> Arc<[Arc<str>]> {
+            a.advisors().iter().map(|t| t.name.clone()).collect()
+        };
+        let mut moved: Vec<Move> = Vec::new();
+        for (index, advisory) in after.0.iter().enumerate() {
+            for s in advisory.students() {
+                if let Some(&before_index) = before_indices.get(&s.name) {
+                    if !same(before_index, index) {
+                        moved.push(Move {
+                            student: s.name.clone(),
+                            from: advisor_names(&self.0[before_index]),
+                            to: advisor_names(advisory),
+                        });
+                    }
+                }
+            }
+        }
+        moved.into()
+    }
+
+    /// Match each advisory of a later organization to an advisory of an earlier one
+    ///
+    /// Advisories are matched like [`match_advisor_sets`], falling back to the advisory in the same position
+    fn match_advisories(before: &[Advisory], after: &[Advisory]) -> Vec<Option<usize>> {
+        let advisor_set = |a: &Advisory| -> HashSet<Arc<str>> {
+            a.advisors().iter().map(|t| t.name.clone()).collect()
+        };
+        let before_sets: Vec<HashSet<Arc<str>>> = before.iter().map(advisor_set).collect();
+        let after_sets: Vec<HashSet<Arc<str>>> = after.iter().map(advisor_set).collect();
+        match_advisor_sets(&before_sets, &after_sets, true)
+    }
+
+    /// Place students with the exact solver, giving up after [`Settings::exact_timeout`]
+    ///
+    /// Returns the organization along with the highest total score possible, or `None` if the solver
+    /// couldn't place every student or ran out of time. A solve in progress can't be stopped, so its thread
+    /// keeps working after the timeout until it next checks the deadline. Programs are capped in size so that
+    /// never takes more than a few seconds, and the solver isn't started while too many earlier solves are
+    /// still running
+    async fn generate_exact(
+        form: &Settings,
+        students: Arc<[Student]>,
+        previous: Arc<HashMap<Arc<str>, usize>>,
+    ) -> Option<(Self, i32)> {
+        let timeout = Duration::from_millis(form.exact_timeout);
+        let deadline = Instant::now() + timeout;
+        let settings = form.clone();
+        let Some(solve) = ExactSolve::start() else {
+            log::info!("Too many exact solves are running, keeping greedy result");
+            return None;
+        };
+        let handle = tokio::task::spawn_blocking(move || {
+            // held until the solve finishes, even if the request stopped waiting for it
+            let _solve = solve;
+            let student_count: u16 = students.len() as u16;
+            let advisory_count: u16 = settings.num_advisories;
+            let mut advisories: Vec<Advisory> = Organization::allocate_advisories(
+                &students,
+                &settings.teacher_groupings,
+                &settings.resolve_grade_levels(),
+                &settings.weights,
+            );
+            Organization::assign_teachers(&mut advisories, &settings.teacher_groupings);
+
+            solve_exact(
+                &advisories,
+                &students,
+                &settings,
+                student_count / advisory_count,
+                &previous,
+                deadline,
+            )
+            .map(|(advisories, bound)| (advisories.into(), bound))
+        });
+
+        match tokio::time::timeout(timeout, handle).await {
+            Ok(Ok(Some(result))) => Some(result),
+            Ok(Ok(None)) => {
+                log::info!("Exact solver found no placement in time, keeping greedy result");
+                None
+            }
+            Ok(Err(_)) => None,
+            Err(_) => {
+                log::info!("Exact solver timed out, keeping greedy result");
+                None
+            }
+        }
+    }
+
+    /// Blocking version of [`Organization::generate`], for running on a thread where blocking is allowed
+    pub fn generate_blocking(form: &Settings, students: Arc<[Student]>) -> Result<Self, Error> {
+        Organization::verify_inputs(form, &students)?;
+        Organization::place(form, students, &HashMap::new())
+    }
+
+    /// Check the settings on their own and against the students they are generating advisories for
+    fn verify_inputs(form: &Settings, students: &[Student]) -> Result<(), Error> {
         form.verify()?;
+        form.verify_capacity(students.len())?;
+        form.verify_pins(students)?;
+        form.verify_groups(students)?;
+        form.verify_grades(students)?;
+        form.verify_criteria(students)
+    }
+
+    /// Place students like [`Organization::generate_blocking`], preferring to keep students in the advisory
+    /// that `previous` maps their name to
+    ///
+    /// Students with a previous advisory are placed before newcomers, so they can return to it before it
+    /// fills up. The settings must already have been checked with [`Organization::verify_inputs`]
+    fn place(
+        form: &Settings,
+        students: Arc<[Student]>,
+        previous: &HashMap<Arc<str>, usize>,
+    ) -> Result<Self, Error> {
+        log::trace!("Building advisories");
 
         // define values for later use
         let student_count: u16 = students.len() as u16;
         let advisory_count: u16 = form.num_advisories;
+        let seed = form.seed.unwrap_or_else(|| rand::random::<u32>().into());
+        log::trace!("Generating advisories with seed {}", seed);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let students = form.ordering.apply(&students, &mut rng);
 
         // create vector of advisories to fill
-        let mut advisories: Vec<Advisory> =
-            Organization::allocate_advisories(student_count, advisory_count);
+        let mut advisories: Vec<Advisory> = Organization::allocate_advisories(
+            &students,
+            &form.teacher_groupings,
+            &form.resolve_grade_levels(),
+            &form.weights,
+        );
+
+        Organization::assign_teachers(&mut advisories, &form.teacher_groupings);
+
+        // place pinned students first, so the quotas they take up are accounted for when scoring the rest
+        // a group goes wherever one of its members is pinned
+        let pinned = form.pinned_advisories();
+        let groups = form.resolve_groups();
+        let mut unpinned: Vec<Vec<&Student>> = Vec::with_capacity(students.len());
+        for unit in Organization::units(&students, &groups) {
+            match unit.iter().find_map(|s| pinned.get(&s.name)) {
+                Some(&index) => unit
+                    .into_iter()
+                    .for_each(|s| advisories[index].add_student(s.clone())),
+                None => unpinned.push(unit),
+            }
+        }
+        // returning students go before newcomers, otherwise keeping the placement order
+        unpinned.sort_by_key(|unit| !unit.iter().any(|s| previous.contains_key(&s.name)));
 
-        Organization::assign_teachers(&mut advisories, form.teacher_groupings.clone());
+        // pinned and grouped students are never moved once placed
+        let fixed: HashSet<Arc<str>> = pinned
+            .keys()
+            .cloned()
+            .chain(groups.iter().flat_map(|g| g.members.iter().cloned()))
+            .collect();
+
+        // add each unit to an advisory, skipping any advisory with someone they can't be with
+        let mut conflicts: Vec<Conflict> = Vec::new();
+        let mut left: usize = unpinned.iter().map(|unit| unit.len()).sum();
+        for unit in unpinned.iter() {
+            // once the students left are only enough to bring advisories up to their minimum sizes,
+            // only advisories under their minimum are open
+            let needed: usize = advisories.iter().map(|a| a.students_needed()).sum();
+            let filling_minimums = left <= needed;
+            left -= unit.len();
+            let open = |a: &Advisory| {
+                a.has_room_for(unit.len()) && (!filling_minimums || a.students_needed() > 0)
+            };
 
-        // add students to advisories
-        for student in students.iter() {
             let max: Option<usize> = advisories
                 .iter()
-                .map(|target_advisory| {
-                    target_advisory.calculate_weight(
+                .enumerate()
+                .filter(|(_, target_advisory)| {
+                    open(target_advisory)
+                        && !unit.iter().any(|s| target_advisory.has_banned_pairing(s))
+                })
+                .map(|(index, target_advisory)| {
+                    (
+                        index,
+                        Organization::unit_weight(
+                            target_advisory,
+                            index,
+                            unit,
+                            &form.weights,
+                            student_count / advisory_count,
+                            previous,
+                        ),
+                    )
+                })
+                .max_by(|(_, a), (_, b)| a.cmp(b))
+                .map(|(index, _)| index);
+            // only a student on their own can have someone moved out of their way
+            let max = match unit.as_slice() {
+                [student] => max.or_else(|| {
+                    Organization::make_room(
+                        &mut advisories,
                         student,
                         &form.weights,
                         student_count / advisory_count,
+                        open,
+                        &fixed,
                     )
-                })
-                .enumerate()
-                .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
-                .map(|(index, _)| index);
-            if let Some(max) = max {
-                advisories[max].add_student(student.clone());
+                }),
+                _ => max,
+            };
+            match max {
+                Some(max) => unit
+                    .iter()
+                    .for_each(|s| advisories[max].add_student((*s).clone())),
+                None => {
+                    for student in unit.iter() {
+                        let mut banned_pairings: Vec<Arc<str>> = advisories
+                            .iter()
+                            .flat_map(|a| a.banned_names_with(student))
+                            .collect();
+                        banned_pairings.sort();
+                        banned_pairings.dedup();
+                        conflicts.push(Conflict {
+                            student: student.name.clone(),
+                            banned_pairings: banned_pairings.into(),
+                        });
+                    }
+                }
             }
         }
+        if !conflicts.is_empty() {
+            return Err(Error::Infeasible(Infeasibility {
+                conflicts: conflicts.into(),
+            }));
+        }
+
+        // revisit the greedy placements if configured to
+        if let Some(local_search) = &form.local_search {
+            local_search.improve(
+                &mut advisories,
+                &form.weights,
+                student_count / advisory_count,
+                &fixed,
+                previous,
+                &mut rng,
+            );
+        }
 
         Ok(advisories.into())
     }
 }
+
+/// Match each set of advisors in `after` to a set in `before`
+///
+/// Sets are matched to one with exactly the same advisors first, then to one sharing any advisor, and
+/// finally, if `by_position` is set, to the set in the same position. Each set in `before` is matched at
+/// most once
+fn match_advisor_sets(
+    before: &[HashSet<Arc<str>>],
+    after: &[HashSet<Arc<str>>],
+    by_position: bool,
+) -> Vec<Option<usize>> {
+    let rules = if by_position { 3 } else { 2 };
+    let mut matches: Vec<Option<usize>> = vec![None; after.len()];
+    let mut taken = vec![false; before.len()];
+    for rule in 0..rules {
+        for (index, set) in after.iter().enumerate() {
+            if matches[index].is_some() {
+                continue;
+            }
+            let found = (0..before.len())
+                .filter(|&b| !taken[b])
+                .find(|&b| match rule {
+                    0 => before[b] == *set,
+                    1 => !before[b].is_disjoint(set),
+                    _ => b == index,
+                });
+            if let Some(b) = found {
+                matches[index] = Some(b);
+                taken[b] = true;
+            }
+        }
+    }
+    matches
+}
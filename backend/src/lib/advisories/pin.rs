@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Student that must be placed in the advisory of a particular advisor
+///
+/// Pinned students are placed before anyone else and are never moved afterwards
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct Pin {
+    /// Name of the pinned student
+    pub student: Arc<str>,
+    /// Name of an advisor in the grouping that the student must be placed with
+    pub advisor: Arc<str>,
+}
+
+impl Pin {
+    /// Creates a pin keeping a student with an advisor
+    pub fn new<T: Into<Arc<str>>, U: Into<Arc<str>>>(student: T, advisor: U) -> Self {
+        Self {
+            student: student.into(),
+            advisor: advisor.into(),
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use crate::advisories::{BestOrganization, Organization, Settings};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Form for regenerating advisories from a previously generated organization
+#[derive(Deserialize, Serialize)]
+pub struct RegenerationForm {
+    /// Settings to generate the new organization with
+    pub settings: Settings,
+    /// Organization that students should be kept in where possible
+    pub previous: Organization,
+}
+
+/// Organization regenerated from a previous one, along with what changed between them
+#[derive(Deserialize, Serialize)]
+pub struct Regeneration {
+    /// The regenerated organization and its score
+    #[serde(flatten)]
+    pub generated: BestOrganization,
+    /// Students from the previous organization that are now in a different advisory
+    pub moved: Arc<[Move]>,
+    /// Students that weren't in the previous organization
+    pub added: Arc<[Arc<str>]>,
+    /// Students from the previous organization that are no longer among the students
+    pub removed: Arc<[Arc<str>]>,
+}
+
+/// Student that is in a different advisory than before regenerating
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Move {
+    /// Name of the student that moved
+    pub student: Arc<str>,
+    /// Names of the advisors of the advisory the student was in before
+    pub from: Arc<[Arc<str>]>,
+    /// Names of the advisors of the advisory the student is in now
+    pub to: Arc<[Arc<str>]>,
+}
@@ -0,0 +1,339 @@
+use crate::{
+    advisories::{Organization, Settings},
+    people::Student,
+    DatabaseNode,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Names of the advisors and students of a saved advisory
+pub(crate) type Placement = (Vec<Arc<str>>, Vec<Arc<str>>);
+
+/// Query creating the `(:Advisory)` nodes of the organization `o` from the `$advisors` and `$students` names
+///
+/// Advisors and students are linked to their advisory if they are still in the database
+const CREATE_ADVISORIES: &str = "WITH o UNWIND range(0, size($advisors) - 1) AS index \
+    CREATE (a:Advisory { index: index, advisors: $advisors[index], students: $students[index], user_id: $user_id })-[:PART_OF]->(o) \
+    WITH a, index \
+    OPTIONAL MATCH (t:Teacher { user_id: $user_id }) WHERE t.name IN $advisors[index] \
+    FOREACH (teacher IN CASE WHEN t IS NULL THEN [] ELSE [t] END | MERGE (teacher)-[:ADVISES]->(a)) \
+    WITH DISTINCT a, index \
+    OPTIONAL MATCH (s:Student { user_id: $user_id }) WHERE s.name IN $students[index] \
+    FOREACH (student IN CASE WHEN s IS NULL THEN [] ELSE [s] END | MERGE (student)-[:MEMBER_OF]->(a))";
+
+/// Name, id and creation time of an organization saved in the database
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct VersionInfo {
+    /// Id used to fetch, rename or delete the saved organization
+    pub id: Arc<str>,
+    /// Name given to the saved organization
+    pub name: Arc<str>,
+    /// When the organization was saved, as an RFC 3339 timestamp
+    pub created: Arc<str>,
+}
+
+/// Organization saved in the database, along with the settings it was generated with
+#[derive(Deserialize, Serialize)]
+pub struct SavedOrganization {
+    /// Name, id and creation time of the saved organization
+    #[serde(flatten)]
+    pub info: VersionInfo,
+    /// Settings the organization was generated with
+    pub settings: Settings,
+    /// The saved organization
+    pub organization: Organization,
+}
+
+/// Form for saving an organization under a name
+#[derive(Deserialize, Serialize)]
+pub struct SaveForm {
+    /// Name to save the organization under
+    pub name: Arc<str>,
+    /// Settings the organization was generated with
+    pub settings: Settings,
+    /// Organization to save
+    pub organization: Organization,
+}
+
+/// Form for renaming a saved organization
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct RenameForm {
+    /// New name for the saved organization
+    pub name: Arc<str>,
+}
+
+impl crate::Verify for RenameForm {
+    /// Finds a missing name
+    fn problems(&self) -> Vec<crate::Problem> {
+        let mut problems = Vec::new();
+        if self.name.is_empty() {
+            problems.push(crate::Problem::new("name", "must not be empty"));
+        }
+        problems
+    }
+}
+
+impl crate::Verify for SaveForm {
+    /// Finds a missing name along with every problem with the settings
+    fn problems(&self) -> Vec<crate::Problem> {
+        let mut problems = Vec::new();
+        if self.name.is_empty() {
+            problems.push(crate::Problem::new("name", "must not be empty"));
+        }
+        problems.extend(
+            self.settings
+                .problems()
+                .into_iter()
+                .map(|p| p.within("settings")),
+        );
+        problems
+    }
+}
+
+impl VersionInfo {
+    /// Create the info of an organization being saved now under a name, with a new random id
+    pub(crate) fn new(name: Arc<str>) -> Self {
+        Self {
+            id: random_string::generate(20, "abcdefghijklmnopqrstuvwxyz0123456789").into(),
+            name,
+            created: chrono::Utc::now().to_rfc3339().into(),
+        }
+    }
+
+    /// Read the version info from an `(:Organization)` node
+    fn from_node(node: &neo4rs::Node) -> Result<Self, crate::Error> {
+        match (
+            node.get::<Arc<str>>("id"),
+            node.get::<Arc<str>>("name"),
+            node.get::<Arc<str>>("created"),
+        ) {
+            (Some(id), Some(name), Some(created)) => Ok(Self { id, name, created }),
+            _ => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
+        }
+    }
+}
+
+impl SavedOrganization {
+    /// Get the names of the advisors and of the students of each advisory, in the order they are saved in
+    fn names(organization: &Organization) -> (Vec<Vec<String>>, Vec<Vec<String>>) {
+        let advisors = organization
+            .0
+            .iter()
+            .map(|a| a.advisors().iter().map(|t| t.name.to_string()).collect())
+            .collect();
+        let students = organization
+            .0
+            .iter()
+            .map(|a| a.students().iter().map(|s| s.name.to_string()).collect())
+            .collect();
+        (advisors, students)
+    }
+
+    /// Save an organization as an `(:Organization)` node holding the name, creation time and settings
+    ///
+    /// Each advisory is saved as an `(:Advisory)` node that is `[:PART_OF]` the organization, with its
+    /// advisors linked by `[:ADVISES]` and its students by `[:MEMBER_OF]`. The advisory nodes also keep the
+    /// names of their advisors and students, so saved versions survive people being removed later
+    pub async fn save<T: Into<String> + Send>(
+        graph: &neo4rs::Graph,
+        user_id: T,
+        name: Arc<str>,
+        settings: &Settings,
+        organization: &Organization,
+    ) -> Result<VersionInfo, crate::Error> {
+        let info = VersionInfo::new(name);
+        SavedOrganization::restore(graph, user_id, &info, settings, organization).await?;
+        Ok(info)
+    }
+
+    /// Save an organization under existing version info, keeping the id and creation time it was first saved
+    /// with, such as when it is copied from other storage
+    pub async fn restore<T: Into<String> + Send>(
+        graph: &neo4rs::Graph,
+        user_id: T,
+        info: &VersionInfo,
+        settings: &Settings,
+        organization: &Organization,
+    ) -> Result<u8, crate::Error> {
+        let settings = serde_json::to_string(settings)
+            .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
+        let (advisors, students) = SavedOrganization::names(organization);
+
+        let query = neo4rs::query(&format!(
+            "CREATE (o:Organization {{ id: $id, name: $name, created: $created, settings: $settings, user_id: $user_id }}) {}",
+            CREATE_ADVISORIES
+        ))
+        .param("id", info.id.clone())
+        .param("name", info.name.clone())
+        .param("created", info.created.clone())
+        .param("settings", settings)
+        .param("advisors", advisors)
+        .param("students", students)
+        .param("user_id", user_id.into());
+
+        match graph.run(query).await {
+            Ok(_) => Ok(1),
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
+        }
+    }
+
+    /// List every saved organization, newest first
+    pub async fn list<T: Into<String> + Send>(
+        graph: &neo4rs::Graph,
+        user_id: T,
+    ) -> Result<Arc<[VersionInfo]>, crate::Error> {
+        let query = neo4rs::query(
+            "MATCH (o:Organization { user_id: $user_id }) RETURN o ORDER BY o.created DESC",
+        )
+        .param("user_id", user_id.into());
+
+        match graph.execute(query).await {
+            Ok(mut result) => {
+                let mut versions: Vec<VersionInfo> = Vec::new();
+                while let Ok(Some(row)) = result.next().await {
+                    let node: neo4rs::Node = row
+                        .get("o")
+                        .ok_or(axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
+                    versions.push(VersionInfo::from_node(&node)?);
+                }
+                Ok(versions.into())
+            }
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
+        }
+    }
+
+    /// Fetch a saved organization by its id
+    ///
+    /// Students are filled in from the current student nodes, and anyone that has since been removed is
+    /// kept with only their name
+    pub async fn get<T: Into<String> + Send>(
+        graph: &neo4rs::Graph,
+        user_id: T,
+        id: &str,
+    ) -> Result<Self, crate::Error> {
+        let user_id: String = user_id.into();
+        let query = neo4rs::query(
+            "MATCH (o:Organization { id: $id, user_id: $user_id }) \
+            OPTIONAL MATCH (a:Advisory)-[:PART_OF]->(o) \
+            WITH o, a ORDER BY a.index \
+            RETURN o, collect(a) as advisories",
+        )
+        .param("id", id)
+        .param("user_id", user_id.clone());
+
+        let row = match graph.execute(query).await {
+            Ok(mut result) => match result.next().await {
+                Ok(Some(row)) => row,
+                Ok(None) => return Err(axum::http::StatusCode::NOT_FOUND.into()),
+                Err(_) => return Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
+            },
+            Err(_) => return Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
+        };
+        let node: neo4rs::Node = row
+            .get("o")
+            .ok_or(axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
+        let info = VersionInfo::from_node(&node)?;
+        let settings: Settings = node
+            .get::<String>("settings")
+            .and_then(|settings| serde_json::from_str(&settings).ok())
+            .ok_or(axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
+        let placements = row
+            .get::<Vec<neo4rs::Node>>("advisories")
+            .ok_or(axum::http::StatusCode::INTERNAL_SERVER_ERROR)?
+            .iter()
+            .map(|a| {
+                let names = |key: &str| -> Vec<Arc<str>> {
+                    a.get::<Vec<String>>(key)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(Arc::from)
+                        .collect()
+                };
+                (names("advisors"), names("students"))
+            })
+            .collect::<Vec<Placement>>();
+
+        let students: Arc<[Student]> = Student::get_nodes(graph, user_id).await?;
+        let organization = Organization::rebuild(&settings, &placements, &students);
+        Ok(Self {
+            info,
+            settings,
+            organization,
+        })
+    }
+
+    /// Replace the advisories of a saved organization, keeping its name and settings
+    pub async fn update<T: Into<String> + Send>(
+        graph: &neo4rs::Graph,
+        user_id: T,
+        id: &str,
+        organization: &Organization,
+    ) -> Result<u8, crate::Error> {
+        let (advisors, students) = SavedOrganization::names(organization);
+        let query = neo4rs::query(&format!(
+            "MATCH (o:Organization {{ id: $id, user_id: $user_id }}) \
+            OPTIONAL MATCH (old:Advisory)-[:PART_OF]->(o) \
+            DETACH DELETE old \
+            WITH DISTINCT o {}",
+            CREATE_ADVISORIES
+        ))
+        .param("id", id)
+        .param("advisors", advisors)
+        .param("students", students)
+        .param("user_id", user_id.into());
+
+        match graph.run(query).await {
+            Ok(_) => Ok(1),
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
+        }
+    }
+
+    /// Give a saved organization a new name
+    pub async fn rename<T: Into<String> + Send>(
+        graph: &neo4rs::Graph,
+        user_id: T,
+        id: &str,
+        name: Arc<str>,
+    ) -> Result<VersionInfo, crate::Error> {
+        let query = neo4rs::query(
+            "MATCH (o:Organization { id: $id, user_id: $user_id }) SET o.name = $name RETURN o",
+        )
+        .param("id", id)
+        .param("name", name)
+        .param("user_id", user_id.into());
+
+        match graph.execute(query).await {
+            Ok(mut result) => match result.next().await {
+                Ok(Some(row)) => {
+                    let node: neo4rs::Node = row
+                        .get("o")
+                        .ok_or(axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
+                    VersionInfo::from_node(&node)
+                }
+                Ok(None) => Err(axum::http::StatusCode::NOT_FOUND.into()),
+                Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
+            },
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
+        }
+    }
+
+    /// Delete a saved organization along with its advisories
+    pub async fn delete<T: Into<String> + Send>(
+        graph: &neo4rs::Graph,
+        user_id: T,
+        id: &str,
+    ) -> Result<u8, crate::Error> {
+        let query = neo4rs::query(
+            "MATCH (o:Organization { id: $id, user_id: $user_id }) \
+            OPTIONAL MATCH (a:Advisory)-[:PART_OF]->(o) \
+            DETACH DELETE a, o",
+        )
+        .param("id", id)
+        .param("user_id", user_id.into());
+
+        match graph.run(query).await {
+            Ok(_) => Ok(1),
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Breakdown of the weighted values that make up a score
+///
+/// Each field is one of the terms of [`crate::advisories::Advisory`]'s weight calculation, already
+/// multiplied by its [`crate::advisories::Weights`] value, and `total` is their sum
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Score {
+    /// Value from students having one of the advisors as a teacher
+    pub teacher: i32,
+    /// Value from the remaining quota for each student's gender
+    pub sex: i32,
+    /// Value from the remaining quota for each student's value of the other balanced attributes
+    #[serde(default)]
+    pub attributes: i32,
+    /// Value from the custom balancing criteria
+    #[serde(default)]
+    pub criteria: i32,
+    /// Value from the remaining quota for each student's grade
+    pub grade: i32,
+    /// Value from the remaining overall person quota
+    pub people: i32,
+    /// Penalty from students placed with a student or advisor they are banned from being with
+    pub banned: i32,
+    /// Value from students placed with at least one of the peers they asked to be with
+    #[serde(default)]
+    pub preferred: i32,
+    /// Value from students advised by at least one of the teachers they asked to be advised by
+    #[serde(default)]
+    pub preferred_advisor: i32,
+    /// Value from students kept in the advisory they were in before regenerating
+    ///
+    /// Always zero outside of [`crate::advisories::Organization::regenerate`]
+    #[serde(default)]
+    pub stability: i32,
+    /// Sum of every other term
+    pub total: i32,
+}
+
+impl std::ops::Add for Score {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            teacher: self.teacher + rhs.teacher,
+            sex: self.sex + rhs.sex,
+            attributes: self.attributes + rhs.attributes,
+            criteria: self.criteria + rhs.criteria,
+            grade: self.grade + rhs.grade,
+            people: self.people + rhs.people,
+            banned: self.banned + rhs.banned,
+            preferred: self.preferred + rhs.preferred,
+            preferred_advisor: self.preferred_advisor + rhs.preferred_advisor,
+            stability: self.stability + rhs.stability,
+            total: self.total + rhs.total,
+        }
+    }
+}
+
+impl std::ops::Sub for Score {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self {
+            teacher: self.teacher - rhs.teacher,
+            sex: self.sex - rhs.sex,
+            attributes: self.attributes - rhs.attributes,
+            criteria: self.criteria - rhs.criteria,
+            grade: self.grade - rhs.grade,
+            people: self.people - rhs.people,
+            banned: self.banned - rhs.banned,
+            preferred: self.preferred - rhs.preferred,
+            preferred_advisor: self.preferred_advisor - rhs.preferred_advisor,
+            stability: self.stability - rhs.stability,
+            total: self.total - rhs.total,
+        }
+    }
+}
+
+impl std::iter::Sum for Score {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |a, b| a + b)
+    }
+}
+
+/// Score of a whole [`crate::advisories::Organization`]
+///
+/// Two organizations generated from the same students and [`crate::advisories::Weights`] can be compared by
+/// their totals
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct OrganizationScore {
+    /// Sum of the scores of every advisory
+    pub total: Score,
+    /// Score of each advisory, in the same order as the organization's advisories
+    pub advisories: Arc<[Score]>,
+    /// How many students' requests to be placed with a peer or advised by a teacher were honored
+    #[serde(default)]
+    pub preferences: PreferenceReport,
+}
+
+/// Count of the students that asked to be placed with a peer, and of those placed with at least one, along
+/// with the same counts for students that asked for an advisor
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PreferenceReport {
+    /// Students that asked to be placed with at least one peer
+    pub requested: usize,
+    /// Students placed with at least one of the peers they asked for
+    pub honored: usize,
+    /// Students that asked to be advised by at least one teacher
+    #[serde(default)]
+    pub advisors_requested: usize,
+    /// Students advised by at least one of the teachers they asked for
+    #[serde(default)]
+    pub advisors_honored: usize,
+}
+
+/// Total score of a single run when generating advisories several times
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunScore {
+    /// Seed the run was generated with
+    pub seed: u64,
+    /// Total score of the run, or none if its students couldn't all be placed
+    pub total: Option<i32>,
+}
@@ -1,6 +1,13 @@
-use crate::{advisories::Weights, people::Teacher};
+use crate::{
+    advisories::{
+        algorithm::MAX_EXACT_ASSIGNMENTS, Algorithm, LocalSearch, Pin, PlacementOrder,
+        TeacherGrouping, Weights,
+    },
+    people::{GradeLevels, Group, Student},
+    Problem,
+};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 /// Form for [`crate::advisories::Advisory`]'s input
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -9,33 +16,585 @@ pub struct Settings {
     pub weights: Weights,
     /// Number of advisories to be generated
     pub num_advisories: u16,
-    /// Groupings of teachers for advisories
-    pub teacher_groupings: Arc<[Arc<[Teacher]>]>,
+    /// Groupings of teachers for advisories, along with any limits on each advisory's size
+    ///
+    /// The handlers group the account's teachers with [`TeacherGrouping::form`] if none are given
+    #[serde(default)]
+    pub teacher_groupings: Arc<[TeacherGrouping]>,
+    /// Seed for the random parts of generation
+    ///
+    /// A random seed is used if none is given, so pass one to get the same advisories every time
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Order that students are placed into advisories in
+    #[serde(default)]
+    pub ordering: PlacementOrder,
+    /// Number of times to generate advisories when only the best scoring result is kept
+    ///
+    /// Each run uses the next seed after the previous one's, and runs after the first are shuffled when
+    /// students would otherwise be placed in input order
+    #[serde(default = "default_runs")]
+    pub runs: u16,
+    /// Budget for improving the advisories after every student is placed
+    ///
+    /// The improvement pass is skipped if none is given
+    #[serde(default)]
+    pub local_search: Option<LocalSearch>,
+    /// Algorithm used to place students
+    #[serde(default)]
+    pub algorithm: Algorithm,
+    /// Milliseconds to wait for [`Algorithm::Exact`] before falling back to the greedy result
+    #[serde(default = "default_exact_timeout")]
+    pub exact_timeout: u64,
+    /// Students that must be placed with a particular advisor, placed before everyone else
+    #[serde(default)]
+    pub pins: Arc<[Pin]>,
+    /// Grade levels that students can be in, with a grade quota in each advisory for every level
+    ///
+    /// The handlers fill in the levels configured for the account if none are given
+    #[serde(default)]
+    pub grade_levels: Option<GradeLevels>,
+    /// Groups of students that must be placed in the same advisory
+    ///
+    /// The handlers fill in the groups saved for the account if none are given
+    #[serde(default)]
+    pub groups: Option<Arc<[Group]>>,
+}
+
+/// Default number of runs, which only generates advisories once
+fn default_runs() -> u16 {
+    1
+}
+
+/// Default time to wait for the exact solver, which is ten seconds
+fn default_exact_timeout() -> u64 {
+    10_000
+}
+
+/// Longest time that the exact solver is allowed to run for, which is one minute
+const MAX_EXACT_TIMEOUT: u64 = 60_000;
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            weights: Weights::default(),
+            num_advisories: 0,
+            teacher_groupings: Arc::from([]),
+            seed: None,
+            ordering: PlacementOrder::default(),
+            runs: default_runs(),
+            local_search: None,
+            algorithm: Algorithm::default(),
+            exact_timeout: default_exact_timeout(),
+            pins: Arc::from([]),
+            grade_levels: None,
+            groups: None,
+        }
+    }
+}
+
+impl Settings {
+    /// Pick a random seed if none was given and return the seed that generation will use
+    ///
+    /// The returned seed can be passed back in to reproduce the same advisories
+    pub fn resolve_seed(&mut self) -> u64 {
+        // random seeds are kept small enough to survive a round trip through a JavaScript number
+        *self
+            .seed
+            .get_or_insert_with(|| rand::random::<u32>().into())
+    }
+
+    /// Get the grade levels to generate advisories with, which are the high school grades if none were given
+    pub fn resolve_grade_levels(&self) -> GradeLevels {
+        self.grade_levels.clone().unwrap_or_default()
+    }
+
+    /// Get the groups of students to keep together, which is none if none were given
+    pub fn resolve_groups(&self) -> Arc<[Group]> {
+        self.groups.clone().unwrap_or_else(|| Arc::from([]))
+    }
+
+    /// Check that every grouped student is among the given students, that no group has a banned pairing
+    /// between its members, and that no group is split up by pins or pinned to an advisory it doesn't fit in
+    ///
+    /// Returns [`crate::Error::Invalid`] with every problem that was found, so errors can be passed through to
+    /// handlers
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use advisory_backend_lib::{advisories::{Pin, Settings, TeacherGrouping}, people::{Group, Student, Teacher}};
+    /// # use std::sync::Arc;
+    /// let students = [
+    ///     Student {
+    ///         name: Arc::from("Student 1"),
+    ///         banned_pairings: Arc::from([Arc::from("Student 2")]),
+    ///         ..Default::default()
+    ///     },
+    ///     Student {
+    ///         name: Arc::from("Student 2"),
+    ///         ..Default::default()
+    ///     },
+    /// ];
+    /// let settings = Settings {
+    ///     groups: Some(Arc::from([Group {
+    ///         name: Arc::from("Twins"),
+    ///         members: Arc::from([Arc::from("Student 1"), Arc::from("Student 2")]),
+    ///     }])),
+    ///     ..Default::default()
+    /// };
+    /// assert!(settings.verify_groups(&students).is_err());
+    ///
+    /// let students = [
+    ///     Student {
+    ///         name: Arc::from("Student 1"),
+    ///         ..Default::default()
+    ///     },
+    ///     Student {
+    ///         name: Arc::from("Student 2"),
+    ///         ..Default::default()
+    ///     },
+    /// ];
+    /// let settings = Settings {
+    ///     teacher_groupings: Arc::from([TeacherGrouping {
+    ///         max_students: Some(1),
+    ///         ..TeacherGrouping::new([Teacher::new("Teacher 1")])
+    ///     }]),
+    ///     pins: Arc::from([Pin::new("Student 1", "Teacher 1")]),
+    ///     ..settings
+    /// };
+    /// assert!(settings.verify_groups(&students).is_err());
+    /// ```
+    pub fn verify_groups(&self, students: &[Student]) -> Result<(), crate::Error> {
+        let pinned = self.pinned_advisories();
+        let mut problems = Vec::new();
+        for (index, group) in self.resolve_groups().iter().enumerate() {
+            let field = format!("groups[{}].members", index);
+            let members: Vec<&Student> = students
+                .iter()
+                .filter(|s| group.members.contains(&s.name))
+                .collect();
+            for m in group.members.iter() {
+                if !members.iter().any(|s| &s.name == m) {
+                    let message = format!("{} is not one of the students", m);
+                    problems.push(Problem::new(field.clone(), message).named(&group.name));
+                }
+            }
+
+            for s in members.iter() {
+                for banned in s.banned_pairings.iter() {
+                    if group.members.contains(banned) {
+                        let message = format!("{} is banned from being with {}", s.name, banned);
+                        problems.push(Problem::new(field.clone(), message).named(&group.name));
+                    }
+                }
+            }
+            let mut pins = group.members.iter().filter_map(|m| pinned.get(m));
+            match pins.next() {
+                Some(first) if pins.any(|index| index != first) => {
+                    let message = "members are pinned to different advisories";
+                    problems.push(Problem::new(field, message).named(&group.name));
+                }
+                Some(&first) => {
+                    // the whole group follows its pinned members, so it has to fit in their advisory
+                    let max = self.teacher_groupings[first].max_students;
+                    if let Some(max) = max.filter(|&max| group.members.len() > max as usize) {
+                        let message = format!(
+                            "has {} members but the advisory they are pinned to holds at most {}",
+                            group.members.len(),
+                            max
+                        );
+                        problems.push(Problem::new(field, message).named(&group.name));
+                    }
+                }
+                None => {}
+            }
+        }
+        crate::Error::check(problems)
+    }
+
+    /// Find students that are in more than one group, and groups that don't fit in any advisory
+    fn group_size_problems(&self) -> Vec<Problem> {
+        let groups = self.resolve_groups();
+        let mut problems = Vec::new();
+        let mut grouped: Vec<&Arc<str>> = Vec::new();
+        for (index, g) in groups.iter().enumerate() {
+            for m in g.members.iter() {
+                if grouped.contains(&m) {
+                    let message = format!("{} is in more than one group", m);
+                    let field = format!("groups[{}].members", index);
+                    problems.push(Problem::new(field, message).named(&g.name));
+                }
+                grouped.push(m);
+            }
+            let fits = self
+                .teacher_groupings
+                .iter()
+                .any(|t| t.max_students.unwrap_or(u16::MAX) as usize >= g.members.len());
+            if !fits {
+                let message = "has more members than any advisory can hold";
+                let field = format!("groups[{}].members", index);
+                problems.push(Problem::new(field, message).named(&g.name));
+            }
+        }
+        problems
+    }
+
+    /// Check that every student is in one of the grade levels
+    ///
+    /// Returns [`crate::Error::Invalid`] with every problem that was found, so errors can be passed through to
+    /// handlers
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use advisory_backend_lib::{advisories::Settings, people::{Grade, GradeLevels, Student}};
+    /// # use std::sync::Arc;
+    /// let students = [Student {
+    ///     name: Arc::from("Student 1"),
+    ///     grade: Grade(7),
+    ///     ..Default::default()
+    /// }];
+    /// let mut settings = Settings::default();
+    /// assert!(settings.verify_grades(&students).is_err());
+    ///
+    /// settings.grade_levels = Some(GradeLevels { levels: Arc::from([Grade(6), Grade(7), Grade(8)]) });
+    /// assert!(settings.verify_grades(&students).is_ok());
+    /// ```
+    pub fn verify_grades(&self, students: &[Student]) -> Result<(), crate::Error> {
+        self.resolve_grade_levels().check_grades(students)
+    }
+
+    /// Check that every student's value for a numeric criterion's field is a number
+    ///
+    /// Returns [`crate::Error::Invalid`] with every problem that was found, so errors can be passed through to
+    /// handlers
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use advisory_backend_lib::{advisories::{BalanceRule, Criterion, FieldKind, Settings, Weights}, people::Student};
+    /// # use std::sync::Arc;
+    /// let students = [Student {
+    ///     name: Arc::from("Student 1"),
+    ///     attributes: [(Arc::from("support"), Arc::from("high"))].into_iter().collect(),
+    ///     ..Default::default()
+    /// }];
+    /// let settings = Settings {
+    ///     weights: Weights {
+    ///         criteria: Arc::from([Criterion {
+    ///             field: Arc::from("support"),
+    ///             kind: FieldKind::Numeric,
+    ///             rule: BalanceRule::AtMost { max: 6 },
+    ///             weight: 5,
+    ///         }]),
+    ///         ..Default::default()
+    ///     },
+    ///     ..Default::default()
+    /// };
+    /// assert!(settings.verify_criteria(&students).is_err());
+    /// ```
+    pub fn verify_criteria(&self, students: &[Student]) -> Result<(), crate::Error> {
+        let mut problems = Vec::new();
+        for (index, s) in students.iter().enumerate() {
+            for c in self.weights.criteria.iter().filter(|c| !c.accepts(s)) {
+                let field = format!("attributes.{}", c.field);
+                problems.push(
+                    Problem::new(field, "must be a number")
+                        .at(index)
+                        .named(&s.name),
+                );
+            }
+        }
+        crate::Error::check(problems)
+    }
+
+    /// Map the name of each pinned student to the index of the advisory they are pinned to
+    ///
+    /// Pins to advisors that aren't in any grouping are left out
+    pub(crate) fn pinned_advisories(&self) -> HashMap<Arc<str>, usize> {
+        self.pins
+            .iter()
+            .filter_map(|pin| {
+                self.teacher_groupings
+                    .iter()
+                    .position(|g| g.teachers.iter().any(|t| t.name == pin.advisor))
+                    .map(|index| (pin.student.clone(), index))
+            })
+            .collect()
+    }
+
+    /// Check that every pinned student is among the given students and that no pin places a student with
+    /// someone they have a banned pairing with
+    ///
+    /// Returns [`crate::Error::Invalid`] with every problem that was found, so errors can be passed through to
+    /// handlers
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use advisory_backend_lib::{advisories::{Pin, Settings, TeacherGrouping}, people::{Student, Teacher}};
+    /// # use std::sync::Arc;
+    /// let students = [Student {
+    ///     name: Arc::from("Student 1"),
+    ///     banned_advisors: Arc::from([Arc::from("Teacher 1")]),
+    ///     ..Default::default()
+    /// }];
+    /// let settings = Settings {
+    ///     num_advisories: 1,
+    ///     teacher_groupings: Arc::from([TeacherGrouping::new([Teacher::new("Teacher 1")])]),
+    ///     pins: Arc::from([Pin::new("Student 1", "Teacher 1")]),
+    ///     ..Default::default()
+    /// };
+    /// assert!(settings.verify_pins(&students).is_err());
+    /// ```
+    pub fn verify_pins(&self, students: &[Student]) -> Result<(), crate::Error> {
+        let pinned = self.pinned_advisories();
+        let mut problems = Vec::new();
+        let mut pinned_students: Vec<(&Student, usize)> = Vec::new();
+        for (position, pin) in self.pins.iter().enumerate() {
+            let index = match pinned.get(&pin.student) {
+                Some(&index) => index,
+                None => continue,
+            };
+            match students.iter().find(|s| s.name == pin.student) {
+                Some(student) => pinned_students.push((student, index)),
+                None => {
+                    let message = format!("{} is not one of the students", pin.student);
+                    problems.push(Problem::new("student", message).at(position));
+                }
+            }
+        }
+        let problems = problems.into_iter().map(|p| p.within("pins"));
+        let mut problems: Vec<Problem> = problems.collect();
+
+        for (position, &(student, index)) in pinned_students.iter().enumerate() {
+            for t in self.teacher_groupings[index].teachers.iter() {
+                if student.banned_advisors.contains(&t.name) {
+                    let message = format!("is banned from being advised by {}", t.name);
+                    problems.push(Problem::new("pins", message).named(&student.name));
+                }
+            }
+            let banned_students = pinned_students[position + 1..]
+                .iter()
+                .filter(|(_, other_index)| *other_index == index)
+                .filter(|(other, _)| {
+                    student.banned_pairings.contains(&other.name)
+                        || other.banned_pairings.contains(&student.name)
+                });
+            for (other, _) in banned_students {
+                let message = format!(
+                    "is pinned to the same advisory as {}, who they are banned from being with",
+                    other.name
+                );
+                problems.push(Problem::new("pins", message).named(&student.name));
+            }
+        }
+        crate::Error::check(problems)
+    }
+
+    /// Find pins to advisors that aren't in any of the groupings, students that are pinned twice, and
+    /// advisories with more pinned students than their size limit
+    fn pin_advisor_problems(&self) -> Vec<Problem> {
+        let pinned = self.pinned_advisories();
+        let mut problems = Vec::new();
+        for (position, pin) in self.pins.iter().enumerate() {
+            let field = format!("pins[{}]", position);
+            let grouped = self
+                .teacher_groupings
+                .iter()
+                .any(|g| g.teachers.iter().any(|t| t.name == pin.advisor));
+            if !grouped {
+                let message = format!("{} is not in any of the teacher groupings", pin.advisor);
+                problems.push(Problem::new("advisor", message).within(&field));
+            } else if self.pins[..position]
+                .iter()
+                .any(|p| p.student == pin.student)
+            {
+                let message = format!("{} is pinned more than once", pin.student);
+                problems.push(Problem::new("student", message).within(&field));
+            }
+        }
+
+        let mut pin_counts = vec![0; self.teacher_groupings.len()];
+        for &index in pinned.values() {
+            pin_counts[index] += 1;
+        }
+        for (index, (g, count)) in self.teacher_groupings.iter().zip(pin_counts).enumerate() {
+            if let Some(max) = g.max_students.filter(|&max| count > max as usize) {
+                let field = format!("teacher_groupings[{}].max_students", index);
+                let message = format!(
+                    "{} students are pinned to an advisory that holds {}",
+                    count, max
+                );
+                problems.push(Problem::new(field, message));
+            }
+        }
+        problems
+    }
+
+    /// Check that the advisories' size limits can hold the given number of students, and that the exact
+    /// algorithm isn't asked to place more students than it can solve for
+    ///
+    /// Returns [`crate::Error::Invalid`] with every problem that was found, so errors can be passed through to
+    /// handlers
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use advisory_backend_lib::{advisories::{Algorithm, Settings, TeacherGrouping}, people::Teacher};
+    /// # use std::sync::Arc;
+    /// let settings = Settings {
+    ///     num_advisories: 2,
+    ///     teacher_groupings: Arc::from([
+    ///         TeacherGrouping {
+    ///             max_students: Some(10),
+    ///             ..TeacherGrouping::new([Teacher::new("Teacher 1")])
+    ///         },
+    ///         TeacherGrouping {
+    ///             max_students: Some(12),
+    ///             ..TeacherGrouping::new([Teacher::new("Teacher 2")])
+    ///         },
+    ///     ]),
+    ///     ..Default::default()
+    /// };
+    /// assert!(settings.verify_capacity(22).is_ok());
+    /// assert!(settings.verify_capacity(23).is_err());
+    ///
+    /// let exact = Settings {
+    ///     num_advisories: 20,
+    ///     teacher_groupings: (0..20)
+    ///         .map(|i| TeacherGrouping::new([Teacher::new(format!("Teacher {}", i))]))
+    ///         .collect(),
+    ///     algorithm: Algorithm::Exact,
+    ///     ..Default::default()
+    /// };
+    /// assert!(exact.verify_capacity(300).is_ok());
+    /// assert!(exact.verify_capacity(1000).is_err());
+    /// ```
+    pub fn verify_capacity(&self, student_count: usize) -> Result<(), crate::Error> {
+        let min: usize = self
+            .teacher_groupings
+            .iter()
+            .map(|g| g.min_students.unwrap_or(0) as usize)
+            .sum();
+        // only advisories with a maximum size can run out of room
+        let max: Option<usize> = self
+            .teacher_groupings
+            .iter()
+            .map(|g| g.max_students.map(|max| max as usize))
+            .sum();
+        let mut problems = Vec::new();
+        if student_count < min {
+            let message = format!(
+                "{} students can't fill advisories that need at least {}",
+                student_count, min
+            );
+            problems.push(Problem::new("teacher_groupings", message));
+        }
+        if let Some(max) = max.filter(|&max| student_count > max) {
+            let message = format!(
+                "{} students don't fit in advisories that hold at most {}",
+                student_count, max
+            );
+            problems.push(Problem::new("teacher_groupings", message));
+        }
+        let assignments = student_count * self.num_advisories as usize;
+        if self.algorithm == Algorithm::Exact && assignments > MAX_EXACT_ASSIGNMENTS {
+            let message = format!(
+                "can't be exact for {} students in {} advisories, which is more than {} placements",
+                student_count, self.num_advisories, MAX_EXACT_ASSIGNMENTS
+            );
+            problems.push(Problem::new("algorithm", message));
+        }
+        crate::Error::check(problems)
+    }
 }
 
 impl crate::Verify for Settings {
-    /// Returns an [`axum::http::StatusCode`] type, so errors can be passed through to handlers
+    /// Finds a number of groupings that doesn't match the number of advisories, settings that are out of
+    /// range, such as having no advisories, the exact algorithm with custom criteria, and every problem with
+    /// the weights, local search, groupings, grade levels, groups and pins
     ///
     /// # Example
     ///
     /// ```
-    /// # use advisory_backend_lib::{Verify, advisories::{Settings, Weights}, people::Teacher};
-    /// # fn main() -> Result<(), axum::http::StatusCode> {
+    /// # use advisory_backend_lib::{Verify, advisories::{Settings, TeacherGrouping}, people::Teacher};
+    /// # use std::sync::Arc;
+    /// # fn main() -> Result<(), advisory_backend_lib::Error> {
     /// # let settings = Settings {
-    /// #     weights: Weights::default(),
     /// #     num_advisories: 1,
-    /// #     teacher_groupings: vec![vec![Teacher::default(); 2]],
+    /// #     teacher_groupings: Arc::from([TeacherGrouping::new([Teacher::new("Teacher 1"), Teacher::new("Teacher 2")])]),
+    /// #     ..Default::default()
     /// # };
     /// settings.verify()?;
+    ///
+    /// let no_advisories = Settings::default();
+    /// let problems = no_advisories.problems();
+    /// assert!(problems.iter().any(|p| &*p.field == "num_advisories"));
     /// # Ok(())
     /// # }
     /// ```
-    fn verify(&self) -> Result<(), axum::http::StatusCode> {
+    fn problems(&self) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        if self.num_advisories == 0 {
+            problems.push(Problem::new("num_advisories", "must be at least 1"));
+        }
         if self.num_advisories != self.teacher_groupings.len() as u16 {
-            Err(axum::http::StatusCode::UNPROCESSABLE_ENTITY)
-        } else {
-            self.weights.verify()?;
-            Ok(())
+            let message = format!(
+                "has {} groupings for {} advisories",
+                self.teacher_groupings.len(),
+                self.num_advisories
+            );
+            problems.push(Problem::new("teacher_groupings", message));
+        }
+        if !(1..=100).contains(&self.runs) {
+            problems.push(Problem::new("runs", "must be from 1-100"));
+        }
+        if self.exact_timeout > MAX_EXACT_TIMEOUT {
+            let message = format!("must be at most {}", MAX_EXACT_TIMEOUT);
+            problems.push(Problem::new("exact_timeout", message));
+        }
+        // custom criteria can't be written as quotas, so the exact solver can't score them
+        if self.algorithm == Algorithm::Exact && !self.weights.criteria.is_empty() {
+            problems.push(Problem::new(
+                "algorithm",
+                "can't be exact when the weights have custom criteria",
+            ));
+        }
+
+        problems.extend(
+            self.weights
+                .problems()
+                .into_iter()
+                .map(|p| p.within("weights")),
+        );
+        if let Some(local_search) = &self.local_search {
+            problems.extend(
+                local_search
+                    .problems()
+                    .into_iter()
+                    .map(|p| p.within("local_search")),
+            );
+        }
+        for (index, g) in self.teacher_groupings.iter().enumerate() {
+            let field = format!("teacher_groupings[{}]", index);
+            problems.extend(g.problems().into_iter().map(|p| p.within(&field)));
+        }
+        if let Some(levels) = &self.grade_levels {
+            problems.extend(
+                levels
+                    .problems()
+                    .into_iter()
+                    .map(|p| p.within("grade_levels")),
+            );
+        }
+        for (index, group) in self.resolve_groups().iter().enumerate() {
+            let field = format!("groups[{}]", index);
+            problems.extend(group.problems().into_iter().map(|p| p.within(&field)));
         }
+        problems.extend(self.group_size_problems());
+        problems.extend(self.pin_advisor_problems());
+        problems
     }
 }
@@ -1,4 +1,6 @@
+use crate::{advisories::Criterion, people::GENDER};
 use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, sync::Arc};
 
 /// Weights from 1-10 used to assign importance to each possible parameter in the 'score calculation'
 /// Used by [`crate::advisories::Advisory`]
@@ -8,7 +10,7 @@ pub struct Weights {
     ///
     /// Value from 1-10
     pub has_teacher: i8,
-    /// The relative importance of biological sex diversity within advisories
+    /// The relative importance of balancing students' gender, the [`GENDER`] attribute, within advisories
     ///
     /// Value from 1-10
     pub sex_diverse: i8,
@@ -20,6 +22,42 @@ pub struct Weights {
     ///
     /// Value from 1-10
     pub equal_people: i8,
+    /// The relative importance of keeping students in the advisory they were in before regenerating
+    ///
+    /// Value from 1-10, only used by [`crate::advisories::Organization::regenerate`]
+    #[serde(default = "default_stability")]
+    pub stability: i8,
+    /// The relative importance of placing students with at least one of the peers they asked to be with
+    ///
+    /// Value from 1-10
+    #[serde(default = "default_preferred")]
+    pub preferred: i8,
+    /// The relative importance of placing students in an advisory with at least one of the advisors they
+    /// asked for
+    ///
+    /// Value from 1-10
+    #[serde(default = "default_preferred")]
+    pub preferred_advisor: i8,
+    /// Other attributes of students to balance across advisories, each with the relative importance of
+    /// balancing it
+    ///
+    /// Values from 1-10. Attributes that aren't listed aren't balanced, and gender is always balanced with
+    /// [`Weights::sex_diverse`] instead
+    #[serde(default)]
+    pub attributes: BTreeMap<Arc<str>, i8>,
+    /// Custom rules for balancing students' fields, each with its own weight
+    #[serde(default)]
+    pub criteria: Arc<[Criterion]>,
+}
+
+/// Default stability weight, which is the middle of the allowed range
+fn default_stability() -> i8 {
+    5
+}
+
+/// Default weight of preferred pairings and advisors, which is the middle of the allowed range
+fn default_preferred() -> i8 {
+    5
 }
 
 impl Default for Weights {
@@ -29,23 +67,31 @@ impl Default for Weights {
             sex_diverse: 1,
             grade_diverse: 1,
             equal_people: 1,
+            stability: default_stability(),
+            preferred: default_preferred(),
+            preferred_advisor: default_preferred(),
+            attributes: BTreeMap::new(),
+            criteria: Arc::from([]),
         }
     }
 }
 
 impl crate::Verify for Weights {
-    /// Returns an [`axum::http::StatusCode`] type, so errors can be passed through to handlers
+    /// Finds every weight outside of 1-10, gender listed among the other attributes, and every problem with
+    /// the criteria
     ///
     /// # Examples
     ///
     /// ```
     /// # use advisory_backend_lib::{Verify, advisories::Weights};
-    /// fn func() -> Result<(), axum::http::StatusCode> {
+    /// fn func() -> Result<(), advisory_backend_lib::Error> {
     ///     let weights = Weights {
     ///         has_teacher: 8,
     ///         sex_diverse: 9,
     ///         grade_diverse: 10,
     ///         equal_people: 10,
+    ///         stability: 5,
+    ///         ..Default::default()
     ///     };
     ///     weights.verify()?;
     ///     Ok(())
@@ -55,28 +101,75 @@ impl crate::Verify for Weights {
     ///
     /// ```
     /// # use advisory_backend_lib::{Verify, advisories::Weights};
-    /// fn func() -> Result<(), axum::http::StatusCode> {
+    /// fn func() -> Result<(), advisory_backend_lib::Error> {
     ///     let weights = Weights {
     ///         has_teacher: 8,
     ///         sex_diverse: 11,
     ///         grade_diverse: 10,
     ///         equal_people: 10,
+    ///         stability: 5,
+    ///         ..Default::default()
     ///     };
     ///     weights.verify()?;
     ///     Ok(())
     /// }
     /// assert_ne!(func(), Ok(()))
     /// ```
-    fn verify(&self) -> Result<(), axum::http::StatusCode> {
+    fn problems(&self) -> Vec<crate::Problem> {
         let range = 1..=10;
-        if !(range.contains(&self.has_teacher)
-            && range.contains(&self.sex_diverse)
-            && range.contains(&self.grade_diverse)
-            && range.contains(&self.equal_people))
-        {
-            Err(axum::http::StatusCode::UNPROCESSABLE_ENTITY)
-        } else {
-            Ok(())
+        let weights = [
+            ("has_teacher", self.has_teacher),
+            ("sex_diverse", self.sex_diverse),
+            ("grade_diverse", self.grade_diverse),
+            ("equal_people", self.equal_people),
+            ("stability", self.stability),
+            ("preferred", self.preferred),
+            ("preferred_advisor", self.preferred_advisor),
+        ];
+        let mut problems: Vec<crate::Problem> = weights
+            .into_iter()
+            .filter(|(_, weight)| !range.contains(weight))
+            .map(|(field, _)| crate::Problem::new(field, "must be from 1-10"))
+            .collect();
+        for (name, weight) in self.attributes.iter() {
+            let field = format!("attributes.{}", name);
+            if &**name == GENDER {
+                problems.push(crate::Problem::new(
+                    field,
+                    "is balanced with sex_diverse instead",
+                ));
+            } else if !range.contains(weight) {
+                problems.push(crate::Problem::new(field, "must be from 1-10"));
+            }
+        }
+        for (index, c) in self.criteria.iter().enumerate() {
+            problems.extend(
+                c.problems()
+                    .into_iter()
+                    .map(|p| p.within(&format!("criteria[{}]", index))),
+            );
+        }
+        problems
+    }
+}
+
+impl Weights {
+    /// Get the name and weight of each attribute balanced across advisories, starting with gender
+    pub(crate) fn balanced_attributes(&self) -> Vec<(Arc<str>, i8)> {
+        std::iter::once((Arc::from(GENDER), self.sex_diverse))
+            .chain(
+                self.attributes
+                    .iter()
+                    .map(|(name, &weight)| (name.clone(), weight)),
+            )
+            .collect()
+    }
+
+    /// Get the weight of balancing an attribute, which is zero for attributes that aren't balanced
+    pub(crate) fn attribute_weight(&self, name: &str) -> i8 {
+        match name {
+            GENDER => self.sex_diverse,
+            _ => self.attributes.get(name).copied().unwrap_or(0),
         }
     }
 }
@@ -0,0 +1,202 @@
+use crate::advisories::Infeasibility;
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Problem found with one field of some input, such as a student in a bulk upload without a name
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    /// Path to the field within the record, such as `teachers[0].name`, or empty if the whole record is the
+    /// problem
+    pub field: Arc<str>,
+    /// Position of the offending record, if it was sent in a list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<usize>,
+    /// Name of the offending record, if it has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<Arc<str>>,
+    /// Description of what is wrong with the field
+    pub message: Arc<str>,
+}
+
+impl Problem {
+    /// Creates a problem with a field that isn't part of a list or named record
+    pub fn new<F: Into<Arc<str>>, M: Into<Arc<str>>>(field: F, message: M) -> Self {
+        Self {
+            field: field.into(),
+            index: None,
+            name: None,
+            message: message.into(),
+        }
+    }
+
+    /// Nest the problem's field inside another field, so `name` within `teachers[0]` becomes
+    /// `teachers[0].name`
+    pub fn within(mut self, field: &str) -> Self {
+        self.field = match &*self.field {
+            "" => Arc::from(field),
+            inner if inner.starts_with('[') => Arc::from(format!("{}{}", field, inner)),
+            inner => Arc::from(format!("{}.{}", field, inner)),
+        };
+        self
+    }
+
+    /// Name the record that the problem was found in, unless a record was already named
+    pub fn named(mut self, name: &Arc<str>) -> Self {
+        if self.name.is_none() && !name.is_empty() {
+            self.name = Some(name.clone());
+        }
+        self
+    }
+
+    /// Record the position of the record that the problem was found in, unless one was already recorded
+    pub fn at(mut self, index: usize) -> Self {
+        self.index.get_or_insert(index);
+        self
+    }
+}
+
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.index, &self.name) {
+            (_, Some(name)) => write!(f, "{} ", name)?,
+            (Some(index), None) => write!(f, "#{} ", index)?,
+            (None, None) => {}
+        }
+        match &*self.field {
+            "" => write!(f, "{}", self.message),
+            field => write!(f, "{}: {}", field, self.message),
+        }
+    }
+}
+
+/// Errors returned by the library, which are turned into [JSON problem details](https://www.rfc-editor.org/rfc/rfc9457)
+/// when returned from a handler
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// Failure that only needs a status code, such as a missing database connection
+    Status(StatusCode),
+    /// Input that didn't pass validation, along with every problem that was found in it
+    Invalid(Arc<[Problem]>),
+    /// Students that couldn't be placed without breaking a banned pairing or size limit
+    Infeasible(Infeasibility),
+    /// Records in the database that couldn't be read, such as nodes written by an older version, along with
+    /// what was wrong with each of them
+    Corrupt(Arc<[Problem]>),
+}
+
+impl Error {
+    /// Fail with [`Error::Invalid`] if any problems were found
+    pub fn check(problems: Vec<Problem>) -> Result<(), Self> {
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(Self::Invalid(problems.into()))
+        }
+    }
+
+    /// Fail with [`Error::Corrupt`] if any records in the database couldn't be read
+    pub fn check_records(problems: Vec<Problem>) -> Result<(), Self> {
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(Self::Corrupt(problems.into()))
+        }
+    }
+
+    /// Status code that the error is returned with
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::Status(code) => *code,
+            Self::Invalid(_) | Self::Infeasible(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::Corrupt(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl From<StatusCode> for Error {
+    fn from(value: StatusCode) -> Self {
+        Self::Status(value)
+    }
+}
+
+impl From<Infeasibility> for Error {
+    fn from(value: Infeasibility) -> Self {
+        Self::Infeasible(value)
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Status(code) => write!(f, "{}", code),
+            Self::Invalid(problems) => {
+                let problems: Vec<String> = problems.iter().map(|p| p.to_string()).collect();
+                write!(f, "invalid input: {}", problems.join("; "))
+            }
+            Self::Infeasible(report) => write!(f, "{}", report),
+            Self::Corrupt(problems) => {
+                let problems: Vec<String> = problems.iter().map(|p| p.to_string()).collect();
+                write!(f, "unreadable records: {}", problems.join("; "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Body of an error response, following the JSON problem details format
+#[derive(Serialize)]
+struct ProblemDetails<'a> {
+    /// URI identifying the kind of problem, which is always `about:blank` since the status code says enough
+    #[serde(rename = "type")]
+    kind: &'static str,
+    /// Short description of the status code
+    title: &'static str,
+    /// Status code of the response
+    status: u16,
+    /// Description of this particular error
+    detail: String,
+    /// Every problem found with the input when it didn't pass validation, or with the records that couldn't
+    /// be read from the database
+    #[serde(skip_serializing_if = "<[Problem]>::is_empty")]
+    problems: &'a [Problem],
+    /// Students that couldn't be placed, when generation failed
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    infeasibility: Option<&'a Infeasibility>,
+}
+
+impl axum::response::IntoResponse for Error {
+    fn into_response(self) -> axum::response::Response {
+        let status = self.status();
+        let problems = match &self {
+            Self::Invalid(problems) => &problems[..],
+            Self::Corrupt(problems) => {
+                log::warn!("Unable to read records from the database: {}", self);
+                &problems[..]
+            }
+            _ => &[],
+        };
+        let infeasibility = match &self {
+            Self::Infeasible(report) => {
+                log::info!("Generation failed: {}", report);
+                Some(report)
+            }
+            _ => None,
+        };
+        let details = ProblemDetails {
+            kind: "about:blank",
+            title: status.canonical_reason().unwrap_or("Unknown Error"),
+            status: status.as_u16(),
+            detail: self.to_string(),
+            problems,
+            infeasibility,
+        };
+        (
+            status,
+            [(axum::http::header::CONTENT_TYPE, "application/problem+json")],
+            serde_json::to_string(&details).unwrap_or_default(),
+        )
+            .into_response()
+    }
+}
@@ -3,26 +3,44 @@
 
 use std::sync::Arc;
 
+/// Crate-level error type and the problems reported when input doesn't pass validation
+mod error;
+
+pub use error::{Error, Problem};
+
 /// Verify trait for input validation
 pub trait Verify {
+    /// Find every problem with the data in a struct, with each problem's field relative to the struct
+    fn problems(&self) -> Vec<Problem>;
+
     /// Verify whether the data in a struct fits certain defined restraints
-    /// Returns an [`axum::http::StatusCode`] type, so errors can be passed through to handlers
-    fn verify(&self) -> Result<(), axum::http::StatusCode>;
+    /// Returns [`Error::Invalid`] with every problem that was found, so errors can be passed through to
+    /// handlers
+    fn verify(&self) -> Result<(), Error> {
+        Error::check(self.problems())
+    }
 }
 
 impl<T: Verify> Verify for Arc<T> {
-    fn verify(&self) -> Result<(), axum::http::StatusCode> {
-        (**self).verify()
+    fn problems(&self) -> Vec<Problem> {
+        (**self).problems()
     }
 }
 
-impl<T: Verify> Verify for Arc<[T]> {
-    fn verify(&self) -> Result<(), axum::http::StatusCode> {
-        for i in (**self).iter() {
-            i.verify()?;
-        }
+/// Each problem records the position of the record it was found in, so every problem in a bulk upload can
+/// be reported at once
+impl<T: Verify> Verify for [T] {
+    fn problems(&self) -> Vec<Problem> {
+        self.iter()
+            .enumerate()
+            .flat_map(|(index, i)| i.problems().into_iter().map(move |p| p.at(index)))
+            .collect()
+    }
+}
 
-        Ok(())
+impl<T: Verify> Verify for Arc<[T]> {
+    fn problems(&self) -> Vec<Problem> {
+        (**self).problems()
     }
 }
 
@@ -35,14 +53,14 @@ pub trait DatabaseNode {
         graph: &neo4rs::Graph,
         user_id: T,
         no_duplicates: bool,
-    ) -> Result<u8, axum::http::StatusCode>;
+    ) -> Result<u8, Error>;
     /// Add a vector of [`Self`] to the database as individual nodes
     async fn add_multiple_nodes<T: Into<String> + Send>(
         nodes: &[Self],
         graph: &neo4rs::Graph,
         user_id: T,
         no_duplicates: bool,
-    ) -> Result<u8, axum::http::StatusCode>
+    ) -> Result<u8, Error>
     where
         Self: Sized;
     /// Remove any nodes in the database with values that match self
@@ -50,17 +68,17 @@ pub trait DatabaseNode {
         &self,
         graph: &neo4rs::Graph,
         user_id: T,
-    ) -> Result<u8, axum::http::StatusCode>;
+    ) -> Result<u8, Error>;
     /// Remove all nodes in the database of this type
     async fn clear_nodes<T: Into<String> + Send>(
         graph: &neo4rs::Graph,
         user_id: T,
-    ) -> Result<u8, axum::http::StatusCode>;
+    ) -> Result<u8, Error>;
     /// Get all nodes in the database of this type
     async fn get_nodes<T: Into<String> + Send>(
         graph: &neo4rs::Graph,
         user_id: T,
-    ) -> Result<Arc<[Self]>, axum::http::StatusCode>
+    ) -> Result<Arc<[Self]>, Error>
     where
         Self: Sized;
 }
@@ -69,8 +87,32 @@ pub trait DatabaseNode {
 pub mod advisories {
     /// Struct and implementations that represent the concept of an advisory
     mod advisory;
+    /// Enum selecting the algorithm used to place students, and the exact solver
+    mod algorithm;
+    /// Custom rules for balancing students' fields across advisories
+    mod criterion;
+    /// Structs for comparing two organizations
+    mod diff;
+    /// Enums for editing saved organizations by hand and the violations edits can cause
+    mod edit;
+    /// Struct grouping advisors together with limits on their advisory's size
+    mod grouping;
+    /// Structs reporting why students couldn't be placed into advisories
+    mod infeasibility;
+    /// Struct configuring the improvement pass run after advisories are generated
+    mod local_search;
+    /// Enum controlling the order that students are placed in
+    mod ordering;
     /// Struct that represents a vector of advisories and is able to generate advisories
     mod organization;
+    /// Struct locking a student to an advisor's advisory
+    mod pin;
+    /// Structs for regenerating advisories while keeping students where they were
+    mod regeneration;
+    /// Structs and database functions for organizations saved as named versions
+    mod saved;
+    /// Structs breaking down how well advisories fit the configured weights
+    mod score;
     /// Struct representing the data sent to the database to configure the returned advisories
     mod settings;
     /// Struct controlling the relative importance of each criteria for a student and advisory
@@ -78,20 +120,37 @@ pub mod advisories {
 
     // Re-exports of data types defined in modules
     pub use advisory::Advisory;
-    pub use organization::Organization;
+    pub use algorithm::Algorithm;
+    pub use criterion::{BalanceRule, Criterion, FieldKind};
+    pub use diff::{AdvisorChange, DiffForm, OrganizationDiff, OrganizationSource};
+    pub use edit::{Edit, EditResult, Violation};
+    pub use grouping::TeacherGrouping;
+    pub use infeasibility::{Conflict, Infeasibility};
+    pub use local_search::LocalSearch;
+    pub use ordering::PlacementOrder;
+    pub use organization::{BestOrganization, Organization};
+    pub use pin::Pin;
+    pub use regeneration::{Move, Regeneration, RegenerationForm};
+    pub(crate) use saved::Placement;
+    pub use saved::{RenameForm, SaveForm, SavedOrganization, VersionInfo};
+    pub use score::{OrganizationScore, PreferenceReport, RunScore, Score};
     pub use settings::Settings;
     pub use weights::Weights;
 }
 
 /// Data types and implementations for representations of Students, Teachers, and People in general
 pub mod people {
-    /// Enum representing each grade level
+    /// Type and constants for the demographic attributes of students
+    mod attribute;
+    /// Structs representing grade levels and the levels an account uses
     mod grade;
+    /// Struct for groups of students that are always placed together
+    mod group;
     /// Struct and implementations for the abstraction of a Person in general
     /// Almost identical to [`Teacher`], but with slightly different [`crate::DatabaseNode`] implementations
     mod person;
-    /// Enum representing Male & Female
-    mod sex;
+    /// Structs for a student's requests to be placed with a peer or advised by a teacher
+    mod preference;
     /// Struct and implementations for the abstraction of a Student
     /// Implementations of [`crate::DatabaseNode`] include creating relationships with teacher nodes
     mod student;
@@ -100,9 +159,31 @@ pub mod people {
     mod teacher;
 
     // Re-exports of data types defined in modules
-    pub use grade::Grade;
+    pub use attribute::{Attributes, GENDER};
+    pub use grade::{Grade, GradeLevels};
+    pub use group::Group;
     pub use person::Person;
-    pub use sex::Sex;
+    pub use preference::{AdvisorPreference, Preference};
     pub use student::Student;
     pub use teacher::Teacher;
 }
+
+/// Storage for people, their relationships and saved organizations, in the database or in memory
+pub mod storage {
+    /// Function copying an account's data between repositories, for moving off the database
+    mod copy;
+    /// Struct storing everything in memory, for tests and running without a database
+    mod memory;
+    /// Implementation of [`Repository`] for the [`neo4rs`] database
+    mod neo4j;
+    /// Trait covering everything that is stored for an account
+    mod repository;
+    /// Struct storing everything in an embedded SQLite database file
+    mod sqlite;
+
+    // Re-exports of data types defined in modules
+    pub use copy::copy_account;
+    pub use memory::MemoryRepository;
+    pub use repository::Repository;
+    pub use sqlite::SqliteRepository;
+}
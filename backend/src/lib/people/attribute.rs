@@ -0,0 +1,14 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+/// Name of the attribute holding a student's gender
+///
+/// Gender can have any value, and is balanced across advisories with
+/// [`crate::advisories::Weights::sex_diverse`]
+pub const GENDER: &str = "gender";
+
+/// Values of a student's demographic attributes, such as gender, house, campus or language, by the name of
+/// the attribute
+///
+/// Every attribute is optional, and students without a value for a balanced attribute are left out of its
+/// quotas
+pub type Attributes = BTreeMap<Arc<str>, Arc<str>>;
@@ -1,82 +1,266 @@
 use std::sync::Arc;
 
+use crate::people::Student;
 use serde::{Deserialize, Serialize};
 
-/// Representation of possible grades for students
+/// Representation of a student's grade level
 ///
-/// Adding more options requires changing the grade quota tuple in [`crate::advisories::Advisory`] as well as adding the mapping to the implementations
-#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
-pub enum Grade {
+/// Stored as the numeric grade, with kindergarten as `0`, so any division of a school can be represented.
+/// Which grades an account uses is configured with [`GradeLevels`]
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(try_from = "GradeForm", into = "i64")]
+pub struct Grade(pub i64);
+
+impl Grade {
     /// 9th grade
-    /// Represented in database as numeric value `9`
-    Freshman,
+    pub const FRESHMAN: Grade = Grade(9);
     /// 10th grade
-    /// Represented in database as numeric value `10`
-    Sophomore,
+    pub const SOPHOMORE: Grade = Grade(10);
     /// 11th grade
-    /// Represented in database as numeric value `11`
-    Junior,
+    pub const JUNIOR: Grade = Grade(11);
     /// 12th grade
-    /// Represented in database as numeric value `12`
-    Senior,
+    pub const SENIOR: Grade = Grade(12);
+    /// Highest grade level that can be used
+    pub const MAX: i64 = 12;
+}
+
+/// Forms that a [`Grade`] can be sent in
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum GradeForm {
+    /// Numeric grade, such as `6`
+    Level(i64),
+    /// Name of a high school grade, such as `"Freshman"`, as grades were sent before they were numeric
+    Name(Arc<str>),
+}
+
+impl TryFrom<GradeForm> for Grade {
+    type Error = String;
+
+    fn try_from(value: GradeForm) -> Result<Self, Self::Error> {
+        match value {
+            GradeForm::Level(level) => Grade::try_from(level),
+            GradeForm::Name(name) => match &*name {
+                "Freshman" => Ok(Grade::FRESHMAN),
+                "Sophomore" => Ok(Grade::SOPHOMORE),
+                "Junior" => Ok(Grade::JUNIOR),
+                "Senior" => Ok(Grade::SENIOR),
+                _ => Err(format!("unknown grade {}", name)),
+            },
+        }
+    }
 }
 
-/// Mapping for string to [`Grade`] enum used for parsing info from database
-impl From<i64> for Grade {
-    fn from(n: i64) -> Self {
-        match n {
-            9 => Self::Freshman,
-            10 => Self::Sophomore,
-            11 => Self::Junior,
-            12 => Self::Senior,
-            _ => panic!("Grade must be from 9-12"),
+/// Mapping for numeric value to [`Grade`] used for parsing info from database, which fails if the value isn't
+/// a grade that can be used
+///
+/// # Example
+///
+/// ```
+/// # use advisory_backend_lib::people::Grade;
+/// assert_eq!(Grade::try_from(6), Ok(Grade(6)));
+/// assert!(Grade::try_from(13).is_err());
+/// ```
+impl TryFrom<i64> for Grade {
+    type Error = String;
+
+    fn try_from(level: i64) -> Result<Self, Self::Error> {
+        if (0..=Grade::MAX).contains(&level) {
+            Ok(Self(level))
+        } else {
+            Err(format!("grade {} is not from 0-{}", level, Grade::MAX))
         }
     }
 }
 
 impl From<Grade> for i64 {
     fn from(g: Grade) -> Self {
-        match g {
-            Grade::Freshman => 9,
-            Grade::Sophomore => 10,
-            Grade::Junior => 11,
-            Grade::Senior => 12,
-        }
+        g.0
     }
 }
 
 impl From<&Grade> for i64 {
     fn from(g: &Grade) -> Self {
-        match g {
-            Grade::Freshman => 9,
-            Grade::Sophomore => 10,
-            Grade::Junior => 11,
-            Grade::Senior => 12,
+        g.0
+    }
+}
+
+impl std::fmt::Display for Grade {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            0 => write!(f, "Kindergarten"),
+            9 => write!(f, "Freshman"),
+            10 => write!(f, "Sophomore"),
+            11 => write!(f, "Junior"),
+            12 => write!(f, "Senior"),
+            n => write!(f, "Grade {}", n),
         }
     }
 }
 
-impl Into<Arc<str>> for Grade {
-    fn into(self) -> Arc<str> {
-        let string = match self {
-            Self::Freshman => "Freshman",
-            Self::Sophomore => "Sophomore",
-            Self::Junior => "Junior",
-            Self::Senior => "Senior",
-        };
+/// Grade levels that an account's students can be in
+///
+/// Advisories get a grade quota for each level, in the order the levels are listed
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct GradeLevels {
+    /// Grade levels, such as `[6, 7, 8]` for a middle school
+    pub levels: Arc<[Grade]>,
+}
 
-        Arc::from(string)
+/// Default values of the [`GradeLevels`] struct
+impl Default for GradeLevels {
+    /// High school grades, from 9th to 12th
+    fn default() -> Self {
+        Self {
+            levels: Arc::from([
+                Grade::FRESHMAN,
+                Grade::SOPHOMORE,
+                Grade::JUNIOR,
+                Grade::SENIOR,
+            ]),
+        }
     }
 }
 
-impl std::fmt::Display for Grade {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let string = match self {
-            Self::Freshman => "Freshman",
-            Self::Sophomore => "Sophomore",
-            Self::Junior => "Junior",
-            Self::Senior => "Senior",
-        };
-        write!(f, "{}", string)
+impl crate::Verify for GradeLevels {
+    /// Finds an empty list of levels, and every level that is out of range or listed more than once
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use advisory_backend_lib::{Verify, people::{Grade, GradeLevels}};
+    /// # use std::sync::Arc;
+    /// let middle_school = GradeLevels { levels: Arc::from([Grade(6), Grade(7), Grade(8)]) };
+    /// assert!(middle_school.verify().is_ok());
+    ///
+    /// let repeated = GradeLevels { levels: Arc::from([Grade(6), Grade(6)]) };
+    /// assert!(repeated.verify().is_err());
+    /// ```
+    fn problems(&self) -> Vec<crate::Problem> {
+        let mut problems = Vec::new();
+        if self.levels.is_empty() {
+            problems.push(crate::Problem::new(
+                "",
+                "must have at least one grade level",
+            ));
+        }
+        for (index, g) in self.levels.iter().enumerate() {
+            let field = format!("[{}]", index);
+            if !(0..=Grade::MAX).contains(&g.0) {
+                let message = format!("grade {} is not from 0-{}", g.0, Grade::MAX);
+                problems.push(crate::Problem::new(field, message));
+            } else if self.levels[..index].contains(g) {
+                problems.push(crate::Problem::new(field, "is listed more than once"));
+            }
+        }
+        problems
+    }
+}
+
+impl GradeLevels {
+    /// Get the grade levels configured for an account, or the default high school grades if none are
+    pub async fn get<T: Into<String> + Send>(
+        graph: &neo4rs::Graph,
+        user_id: T,
+    ) -> Result<Self, crate::Error> {
+        let query = neo4rs::query("MATCH (g:GradeLevels { user_id: $user_id }) RETURN g")
+            .param("user_id", user_id.into());
+
+        match graph.execute(query).await {
+            Ok(mut result) => match result.next().await {
+                Ok(Some(row)) => {
+                    let levels = row
+                        .get::<neo4rs::Node>("g")
+                        .and_then(|node| node.get::<Vec<i64>>("levels"))
+                        .ok_or_else(|| crate::Problem::new("levels", "is missing"))
+                        .and_then(|levels| {
+                            levels
+                                .into_iter()
+                                .map(Grade::try_from)
+                                .collect::<Result<Arc<[Grade]>, _>>()
+                                .map_err(|message| crate::Problem::new("levels", message))
+                        });
+                    match levels {
+                        Ok(levels) => Ok(Self { levels }),
+                        Err(problem) => Err(crate::Error::Corrupt(Arc::new([problem]))),
+                    }
+                }
+                Ok(None) => Ok(Self::default()),
+                Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
+            },
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
+        }
+    }
+
+    /// Set the grade levels of an account, replacing any that were configured before
+    pub async fn set<T: Into<String> + Send>(
+        &self,
+        graph: &neo4rs::Graph,
+        user_id: T,
+    ) -> Result<u8, crate::Error> {
+        let levels: Vec<i64> = self.levels.iter().map(i64::from).collect();
+        let query =
+            neo4rs::query("MERGE (g:GradeLevels { user_id: $user_id }) SET g.levels = $levels")
+                .param("levels", levels)
+                .param("user_id", user_id.into());
+
+        match graph.run(query).await {
+            Ok(_) => Ok(1),
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
+        }
+    }
+
+    /// Check that every student is in one of the levels, reporting each student that isn't
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use advisory_backend_lib::{Error, people::{Grade, GradeLevels, Student}};
+    /// # use std::sync::Arc;
+    /// let students: Vec<Student> = [Grade(5), Grade(6), Grade(9)]
+    ///     .into_iter()
+    ///     .map(|grade| Student { grade, ..Default::default() })
+    ///     .collect();
+    /// let middle_school = GradeLevels { levels: Arc::from([Grade(6), Grade(7), Grade(8)]) };
+    /// match middle_school.check_grades(&students) {
+    ///     Err(Error::Invalid(problems)) => assert_eq!(problems.len(), 2),
+    ///     _ => panic!("expected two students outside of the grade levels"),
+    /// }
+    /// ```
+    pub fn check_grades(&self, students: &[Student]) -> Result<(), crate::Error> {
+        crate::Error::check(
+            students
+                .iter()
+                .enumerate()
+                .filter_map(|(index, s)| self.grade_problem(s).map(|p| p.at(index)))
+                .collect(),
+        )
+    }
+
+    /// Check that a single student is in one of the levels
+    pub fn check_grade(&self, student: &Student) -> Result<(), crate::Error> {
+        crate::Error::check(self.grade_problem(student).into_iter().collect())
+    }
+
+    /// Problem with a student's grade if it isn't one of the levels
+    fn grade_problem(&self, student: &Student) -> Option<crate::Problem> {
+        (!self.contains(&student.grade)).then(|| {
+            crate::Problem::new(
+                "grade",
+                format!("{} is not one of the grade levels", student.grade),
+            )
+            .named(&student.name)
+        })
+    }
+
+    /// Checks whether a grade is one of the levels
+    pub fn contains(&self, grade: &Grade) -> bool {
+        self.levels.contains(grade)
+    }
+
+    /// Position of a grade in the levels, which is also its position in an advisory's grade quotas
+    pub(crate) fn index_of(&self, grade: &Grade) -> Option<usize> {
+        self.levels.iter().position(|g| g == grade)
     }
 }
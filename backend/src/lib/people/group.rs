@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Students that must always be placed in the same advisory, such as siblings or a cohort from a partner
+/// program
+///
+/// Stored as a `(:Group)` node, with each member linked to it by an `[:IN_GROUP]` relationship
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct Group {
+    /// Name of the group, which distinguishes it from the account's other groups
+    pub name: Arc<str>,
+    /// Names of the students in the group
+    pub members: Arc<[Arc<str>]>,
+}
+
+impl crate::Verify for Group {
+    /// Finds a missing name and every member listed more than once, naming the group in each
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use advisory_backend_lib::{Verify, people::Group};
+    /// # use std::sync::Arc;
+    /// let twins = Group {
+    ///     name: Arc::from("Twins"),
+    ///     members: Arc::from([Arc::from("Student 1"), Arc::from("Student 2")]),
+    /// };
+    /// assert!(twins.verify().is_ok());
+    ///
+    /// let repeated = Group {
+    ///     name: Arc::from("Twins"),
+    ///     members: Arc::from([Arc::from("Student 1"), Arc::from("Student 1")]),
+    /// };
+    /// assert!(repeated.verify().is_err());
+    /// ```
+    fn problems(&self) -> Vec<crate::Problem> {
+        let mut problems = Vec::new();
+        if self.name.is_empty() {
+            problems.push(crate::Problem::new("name", "must not be empty"));
+        }
+        for (index, m) in self.members.iter().enumerate() {
+            if self.members[..index].contains(m) {
+                let field = format!("members[{}]", index);
+                problems.push(crate::Problem::new(
+                    field,
+                    format!("{} is listed more than once", m),
+                ));
+            }
+        }
+        problems.into_iter().map(|p| p.named(&self.name)).collect()
+    }
+}
+
+impl Group {
+    /// Save the group, replacing the members of any group that already has its name
+    pub async fn set<T: Into<String> + Send>(
+        &self,
+        graph: &neo4rs::Graph,
+        user_id: T,
+    ) -> Result<u8, crate::Error> {
+        let members: Vec<String> = self.members.iter().map(|m| m.to_string()).collect();
+        let query = neo4rs::query(
+            "MERGE (g:Group { name: $name, user_id: $user_id }) \
+            WITH g OPTIONAL MATCH (:Student)-[old:IN_GROUP]->(g) DELETE old \
+            WITH DISTINCT g MATCH (s:Student { user_id: $user_id }) WHERE s.name IN $members \
+            MERGE (s)-[:IN_GROUP]->(g)",
+        )
+        .param("name", self.name.clone())
+        .param("members", members)
+        .param("user_id", user_id.into());
+
+        match graph.run(query).await {
+            Ok(_) => Ok(1),
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
+        }
+    }
+
+    /// List every group along with its members, ordered by name
+    pub async fn list<T: Into<String> + Send>(
+        graph: &neo4rs::Graph,
+        user_id: T,
+    ) -> Result<Arc<[Self]>, crate::Error> {
+        let query = neo4rs::query(
+            "MATCH (g:Group { user_id: $user_id }) OPTIONAL MATCH (s:Student)-[:IN_GROUP]->(g) \
+            WITH g, s ORDER BY s.name \
+            RETURN g.name as name, collect(s.name) as members ORDER BY name",
+        )
+        .param("user_id", user_id.into());
+
+        match graph.execute(query).await {
+            Ok(mut result) => {
+                let mut groups: Vec<Self> = Vec::new();
+                while let Ok(Some(row)) = result.next().await {
+                    match (
+                        row.get::<Arc<str>>("name"),
+                        row.get::<Vec<String>>("members"),
+                    ) {
+                        (Some(name), Some(members)) => groups.push(Self {
+                            name,
+                            members: members.into_iter().map(Arc::from).collect(),
+                        }),
+                        _ => return Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
+                    }
+                }
+                Ok(groups.into())
+            }
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
+        }
+    }
+
+    /// Delete a group by its name, leaving its members in the database
+    pub async fn remove<T: Into<String> + Send>(
+        graph: &neo4rs::Graph,
+        user_id: T,
+        name: &str,
+    ) -> Result<u8, crate::Error> {
+        let query =
+            neo4rs::query("MATCH (g:Group { name: $name, user_id: $user_id }) DETACH DELETE g")
+                .param("name", name)
+                .param("user_id", user_id.into());
+
+        match graph.run(query).await {
+            Ok(_) => Ok(1),
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
+        }
+    }
+}
@@ -1,4 +1,7 @@
-use crate::people::{Student, Teacher};
+use crate::{
+    people::{Student, Teacher},
+    Problem,
+};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -17,6 +20,57 @@ impl std::fmt::Display for Person {
     }
 }
 
+/// Node in a column of a database row along with its name, which every person's node has
+///
+/// Fails with a problem on the whole record if the column doesn't hold a node, or on its `name` if the node
+/// has no name
+pub(super) fn named_node(
+    row: &neo4rs::Row,
+    column: &str,
+) -> Result<(neo4rs::Node, Arc<str>), Problem> {
+    let node: neo4rs::Node = row
+        .get(column)
+        .ok_or_else(|| Problem::new("", format!("has no {} node", column)))?;
+    let name: Arc<str> = node
+        .get("name")
+        .ok_or_else(|| Problem::new("name", "is missing"))?;
+    Ok((node, name))
+}
+
+/// Names of the nodes collected into a column of a database row, such as the people someone is banned from
+///
+/// Problems are reported on `field`, the field of the struct that the names are read into
+pub(super) fn node_names(
+    row: &neo4rs::Row,
+    column: &str,
+    field: &str,
+) -> Result<Arc<[Arc<str>]>, Problem> {
+    row.get::<Vec<neo4rs::Node>>(column)
+        .ok_or_else(|| Problem::new(field, "is missing"))?
+        .iter()
+        .map(|n| {
+            n.get::<Arc<str>>("name")
+                .ok_or_else(|| Problem::new(field, "has a person without a name"))
+        })
+        .collect()
+}
+
+/// Read a person from a row returned by [`crate::DatabaseNode::get_nodes`], reporting every field that couldn't
+/// be read
+impl TryFrom<&neo4rs::Row> for Person {
+    type Error = Vec<Problem>;
+
+    fn try_from(row: &neo4rs::Row) -> Result<Self, Self::Error> {
+        let (_, name) = named_node(row, "people").map_err(|p| vec![p])?;
+        let banned_pairings =
+            node_names(row, "banned", "banned_pairings").map_err(|p| vec![p.named(&name)])?;
+        Ok(Self {
+            name,
+            banned_pairings,
+        })
+    }
+}
+
 impl From<Student> for Person {
     fn from(s: Student) -> Self {
         Self {
@@ -36,33 +90,89 @@ impl From<Teacher> for Person {
 }
 
 impl Person {
-    /// Ban two people from being in the same advisory (unless they are both teachers, which
-    /// wouldn't do anything)
+    /// Ban two people from being in the same advisory
+    ///
+    /// Two students are linked by a `[:BANNED_STUDENT]` relationship, and a student and a teacher by a
+    /// `[:BANNED_ADVISOR]` relationship from the student to the teacher. Two teachers are linked by a
+    /// `[:BANNED_COADVISOR]` relationship, which only keeps them apart when advisors are grouped
+    /// automatically
     pub async fn ban_pair<T: Into<String> + Send>(
         form: [Self; 2],
         graph: &neo4rs::Graph,
         user_id: T,
         no_duplicates: bool,
-    ) -> Result<u8, axum::http::StatusCode> {
-        let query_string = match no_duplicates {
-            true => "MERGE (p1)-[:BANNED]-(p2)",
-            false => "CREATE (p1)-[:BANNED]-(p2)",
+    ) -> Result<u8, crate::Error> {
+        let (advisor_string, student_string, coadvisor_string) = match no_duplicates {
+            true => (
+                "MERGE (s)-[:BANNED_ADVISOR]->(other)",
+                "MERGE (s)-[:BANNED_STUDENT]-(other)",
+                "MERGE (s)-[:BANNED_COADVISOR]-(other)",
+            ),
+            false => (
+                "CREATE (s)-[:BANNED_ADVISOR]->(other)",
+                "CREATE (s)-[:BANNED_STUDENT]->(other)",
+                "CREATE (s)-[:BANNED_COADVISOR]->(other)",
+            ),
         };
 
-        // potential for sql injection by directly using the value from banned
-        // that being said, it doesn't work otherwise
-        // maybe look for a way to sanitize inputs or rewrite library (it hasn't been updated in
-        // forever)
-        let query = neo4rs::query(&format!("OPTIONAL MATCH (p1 {{ name: $banned_name, user_id: $user_id }}) OPTIONAL MATCH (p2 {{ name: $banned_name2, user_id: $user_id }}) {}", query_string))
-            .param("user_id", user_id.into())
-            .param("banned_name", form[0].name.clone())
-            .param("banned_name2", form[1].name.clone());
+        // the relationship's type can't be a parameter, so each type is only created when it fits the pair,
+        // and nothing is created when either person is missing
+        let query = neo4rs::query(&format!(
+            "OPTIONAL MATCH (p1 {{ name: $banned_name, user_id: $user_id }}) WHERE p1:Student OR p1:Teacher \
+            OPTIONAL MATCH (p2 {{ name: $banned_name2, user_id: $user_id }}) WHERE p2:Student OR p2:Teacher \
+            WITH p1, p2, CASE WHEN p1:Student THEN p1 ELSE p2 END as s, CASE WHEN p1:Student THEN p2 ELSE p1 END as other \
+            FOREACH (_ IN CASE WHEN s:Student AND other:Teacher THEN [1] ELSE [] END | {}) \
+            FOREACH (_ IN CASE WHEN s:Student AND other:Student THEN [1] ELSE [] END | {}) \
+            FOREACH (_ IN CASE WHEN s:Teacher AND other:Teacher THEN [1] ELSE [] END | {}) \
+            RETURN p1 IS NOT NULL as found, p2 IS NOT NULL as found2",
+            advisor_string, student_string, coadvisor_string
+        ))
+        .param("user_id", user_id.into())
+        .param("banned_name", form[0].name.clone())
+        .param("banned_name2", form[1].name.clone());
 
-        match graph.run(query).await {
-            Ok(_) => Ok(1),
-            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR),
+        match graph.execute(query).await {
+            Ok(mut result) => match result.next().await {
+                Ok(Some(row)) => {
+                    let found = |column: &str| row.get::<bool>(column).unwrap_or(false);
+                    Self::check_stored(&form, [found("found"), found("found2")])?;
+                    Ok(1)
+                }
+                _ => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
+            },
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
         }
     }
+
+    /// Fail with [`crate::Error::Invalid`] on each person of a pair that isn't a stored student or teacher,
+    /// so a ban that couldn't be recorded isn't reported as done
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use advisory_backend_lib::people::Person;
+    /// # use std::sync::Arc;
+    /// let pair = [
+    ///     Person { name: Arc::from("Student 1"), banned_pairings: Arc::from([]) },
+    ///     Person { name: Arc::from("Nobody"), banned_pairings: Arc::from([]) },
+    /// ];
+    /// assert!(Person::check_stored(&pair, [true, true]).is_ok());
+    /// assert!(Person::check_stored(&pair, [true, false]).is_err());
+    /// ```
+    pub fn check_stored(pair: &[Self; 2], stored: [bool; 2]) -> Result<(), crate::Error> {
+        let problems = pair
+            .iter()
+            .zip(stored)
+            .enumerate()
+            .filter(|(_, (_, stored))| !stored)
+            .map(|(index, (person, _))| {
+                Problem::new("name", "is not a student or teacher")
+                    .named(&person.name)
+                    .at(index)
+            })
+            .collect();
+        crate::Error::check(problems)
+    }
 }
 
 #[async_trait::async_trait]
@@ -72,7 +182,7 @@ impl crate::DatabaseNode for Person {
         graph: &neo4rs::Graph,
         user_id: T,
         no_duplicates: bool,
-    ) -> Result<u8, axum::http::StatusCode> {
+    ) -> Result<u8, crate::Error> {
         let query = match no_duplicates {
             true => neo4rs::query("MERGE (p { name: $name, user_id: $user_id })"),
             false => neo4rs::query("CREATE (p { name: $name, user_id: $user_id })"),
@@ -82,7 +192,7 @@ impl crate::DatabaseNode for Person {
 
         match graph.run(query).await {
             Ok(_) => Ok(1),
-            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR),
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
         }
     }
 
@@ -91,40 +201,23 @@ impl crate::DatabaseNode for Person {
         graph: &neo4rs::Graph,
         user_id: T,
         no_duplicates: bool,
-    ) -> Result<u8, axum::http::StatusCode> {
+    ) -> Result<u8, crate::Error> {
         let inside_query = match no_duplicates {
-            true => "MERGE (p { name: person.name, user_id: $user_id })",
-            false => "CREATE (p { name: person.name, user_id: $user_id })",
+            true => "MERGE (p { name: name, user_id: $user_id })",
+            false => "CREATE (p { name: name, user_id: $user_id })",
         };
 
-        let mut parameter_pairs: std::collections::HashMap<String, Arc<str>> =
-            std::collections::HashMap::new();
-        let parameter_list = nodes
-            .iter()
-            .map(|q| {
-                let key = random_string::generate(50, "abcdefghijklmnopqrstuvwxyz");
-                parameter_pairs.insert(key.clone(), q.name.clone());
-                format!("{{ name: ${} }}", key)
-            })
-            .collect::<Vec<_>>()
-            .join(",");
-        let mut query = neo4rs::query(
-            format!(
-                "UNWIND [{}] as person CALL {{ WITH person {} }}",
-                parameter_list, inside_query
-            )
-            .as_str(),
-        )
+        let names: Vec<String> = nodes.iter().map(|n| n.name.to_string()).collect();
+        let query = neo4rs::query(&format!(
+            "UNWIND $names AS name CALL {{ WITH name {} }}",
+            inside_query
+        ))
+        .param("names", names)
         .param("user_id", user_id.into());
 
-        // substitute values in
-        for (key, value) in parameter_pairs {
-            query = query.param(&key, value);
-        }
-
         match graph.run(query).await {
             Ok(_) => Ok(1),
-            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR),
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
         }
     }
 
@@ -132,57 +225,55 @@ impl crate::DatabaseNode for Person {
         &self,
         graph: &neo4rs::Graph,
         user_id: T,
-    ) -> Result<u8, axum::http::StatusCode> {
-        let query = neo4rs::query("MATCH (p { name: $name, user_id: $user_id }) DETACH DELETE p")
-            .param("name", self.name.clone())
-            .param("user_id", user_id.into());
+    ) -> Result<u8, crate::Error> {
+        let query = neo4rs::query(
+            "MATCH (p { name: $name, user_id: $user_id }) WHERE NOT p:Organization AND NOT p:Group DETACH DELETE p",
+        )
+        .param("name", self.name.clone())
+        .param("user_id", user_id.into());
 
         match graph.run(query).await {
             Ok(_) => Ok(1),
-            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR),
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
         }
     }
 
     async fn clear_nodes<T: Into<String> + Send>(
         graph: &neo4rs::Graph,
         user_id: T,
-    ) -> Result<u8, axum::http::StatusCode> {
-        let query = neo4rs::query("MATCH (p { user_id: $user_id }) DETACH DELETE (p)")
+    ) -> Result<u8, crate::Error> {
+        let query = neo4rs::query("MATCH (p { user_id: $user_id }) WHERE NOT p:Organization AND NOT p:Advisory AND NOT p:GradeLevels DETACH DELETE (p)")
             .param("user_id", user_id.into());
 
         match graph.run(query).await {
             Ok(_) => Ok(1),
-            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR),
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
         }
     }
 
     async fn get_nodes<T: Into<String> + Send>(
         graph: &neo4rs::Graph,
         user_id: T,
-    ) -> Result<Arc<[Self]>, axum::http::StatusCode> {
-        let query = neo4rs::query("MATCH (p { user_id: $user_id }) OPTIONAL MATCH (p)-[:BANNED]-(b) RETURN distinct(p) as people, collect(b) as banned")
+    ) -> Result<Arc<[Self]>, crate::Error> {
+        let query = neo4rs::query("MATCH (p { user_id: $user_id }) WHERE NOT p:Organization AND NOT p:Advisory AND NOT p:GradeLevels AND NOT p:Group OPTIONAL MATCH (p)-[:BANNED|BANNED_STUDENT|BANNED_ADVISOR|BANNED_COADVISOR]-(b) RETURN distinct(p) as people, collect(distinct b) as banned")
             .param("user_id", user_id.into());
 
         match graph.execute(query).await {
             Ok(mut result) => {
                 let mut people: Vec<Self> = Vec::new();
+                let mut problems = Vec::new();
+                let mut index = 0;
                 while let Ok(Some(row)) = result.next().await {
-                    let person: neo4rs::Node = row.get("people").unwrap();
-                    let name: Arc<str> = person.get("name").unwrap();
-                    let banned_pairings = row
-                        .get::<Vec<neo4rs::Node>>("banned")
-                        .unwrap()
-                        .iter()
-                        .map(|b| b.get("name").unwrap())
-                        .collect::<Arc<[_]>>();
-                    people.push(Self {
-                        name,
-                        banned_pairings,
-                    })
+                    match Self::try_from(&row) {
+                        Ok(person) => people.push(person),
+                        Err(found) => problems.extend(found.into_iter().map(|p| p.at(index))),
+                    }
+                    index += 1;
                 }
+                crate::Error::check_records(problems)?;
                 Ok(people.into())
             }
-            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR),
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
         }
     }
 }
@@ -0,0 +1,243 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Request from a student to be placed with another student
+///
+/// Stored as a `[:PREFERS]` relationship from the student to the peer they asked for. A student with several
+/// requests only needs one of them honored
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct Preference {
+    /// Name of the student making the request
+    pub student: Arc<str>,
+    /// Name of the student they asked to be placed with
+    pub preferred: Arc<str>,
+}
+
+impl crate::Verify for Preference {
+    /// Finds missing names and a student asking to be placed with themselves
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use advisory_backend_lib::{Verify, people::Preference};
+    /// # use std::sync::Arc;
+    /// let preference = Preference {
+    ///     student: Arc::from("Student 1"),
+    ///     preferred: Arc::from("Student 2"),
+    /// };
+    /// assert!(preference.verify().is_ok());
+    ///
+    /// let themselves = Preference {
+    ///     student: Arc::from("Student 1"),
+    ///     preferred: Arc::from("Student 1"),
+    /// };
+    /// assert!(themselves.verify().is_err());
+    /// ```
+    fn problems(&self) -> Vec<crate::Problem> {
+        let mut problems = Vec::new();
+        if self.student.is_empty() {
+            problems.push(crate::Problem::new("student", "must not be empty"));
+        }
+        if self.preferred.is_empty() {
+            problems.push(crate::Problem::new("preferred", "must not be empty"));
+        } else if self.student == self.preferred {
+            problems.push(crate::Problem::new(
+                "preferred",
+                "must not be the student making the request",
+            ));
+        }
+        problems
+    }
+}
+
+impl Preference {
+    /// Record the request, linking the two students with a `[:PREFERS]` relationship
+    pub async fn add<T: Into<String> + Send>(
+        &self,
+        graph: &neo4rs::Graph,
+        user_id: T,
+    ) -> Result<u8, crate::Error> {
+        let query = neo4rs::query(
+            "MATCH (s:Student { name: $student, user_id: $user_id }) \
+            MATCH (p:Student { name: $preferred, user_id: $user_id }) \
+            MERGE (s)-[:PREFERS]->(p)",
+        )
+        .param("student", self.student.clone())
+        .param("preferred", self.preferred.clone())
+        .param("user_id", user_id.into());
+
+        match graph.run(query).await {
+            Ok(_) => Ok(1),
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
+        }
+    }
+
+    /// List every request, ordered by the name of the student making it
+    pub async fn list<T: Into<String> + Send>(
+        graph: &neo4rs::Graph,
+        user_id: T,
+    ) -> Result<Arc<[Self]>, crate::Error> {
+        let query = neo4rs::query(
+            "MATCH (s:Student { user_id: $user_id })-[:PREFERS]->(p:Student) \
+            RETURN s.name as student, p.name as preferred ORDER BY student, preferred",
+        )
+        .param("user_id", user_id.into());
+
+        match graph.execute(query).await {
+            Ok(mut result) => {
+                let mut preferences: Vec<Self> = Vec::new();
+                while let Ok(Some(row)) = result.next().await {
+                    match (
+                        row.get::<Arc<str>>("student"),
+                        row.get::<Arc<str>>("preferred"),
+                    ) {
+                        (Some(student), Some(preferred)) => {
+                            preferences.push(Self { student, preferred })
+                        }
+                        _ => return Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
+                    }
+                }
+                Ok(preferences.into())
+            }
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
+        }
+    }
+
+    /// Withdraw the request, removing the `[:PREFERS]` relationship
+    pub async fn remove<T: Into<String> + Send>(
+        &self,
+        graph: &neo4rs::Graph,
+        user_id: T,
+    ) -> Result<u8, crate::Error> {
+        let query = neo4rs::query(
+            "MATCH (s:Student { name: $student, user_id: $user_id })-[r:PREFERS]->(p:Student { name: $preferred, user_id: $user_id }) \
+            DELETE r",
+        )
+        .param("student", self.student.clone())
+        .param("preferred", self.preferred.clone())
+        .param("user_id", user_id.into());
+
+        match graph.run(query).await {
+            Ok(_) => Ok(1),
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
+        }
+    }
+}
+
+/// Request from a student to be advised by a particular teacher
+///
+/// Stored as a `[:PREFERS_ADVISOR]` relationship from the student to the teacher. A student with several
+/// requests only needs one of them honored
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct AdvisorPreference {
+    /// Name of the student making the request
+    pub student: Arc<str>,
+    /// Name of the teacher they asked to be advised by
+    pub advisor: Arc<str>,
+}
+
+impl crate::Verify for AdvisorPreference {
+    /// Finds missing names
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use advisory_backend_lib::{Verify, people::AdvisorPreference};
+    /// # use std::sync::Arc;
+    /// let preference = AdvisorPreference {
+    ///     student: Arc::from("Student 1"),
+    ///     advisor: Arc::from("Teacher 1"),
+    /// };
+    /// assert!(preference.verify().is_ok());
+    ///
+    /// let unnamed = AdvisorPreference {
+    ///     student: Arc::from("Student 1"),
+    ///     advisor: Arc::from(""),
+    /// };
+    /// assert!(unnamed.verify().is_err());
+    /// ```
+    fn problems(&self) -> Vec<crate::Problem> {
+        let mut problems = Vec::new();
+        if self.student.is_empty() {
+            problems.push(crate::Problem::new("student", "must not be empty"));
+        }
+        if self.advisor.is_empty() {
+            problems.push(crate::Problem::new("advisor", "must not be empty"));
+        }
+        problems
+    }
+}
+
+impl AdvisorPreference {
+    /// Record the request, linking the student to the teacher with a `[:PREFERS_ADVISOR]` relationship
+    pub async fn add<T: Into<String> + Send>(
+        &self,
+        graph: &neo4rs::Graph,
+        user_id: T,
+    ) -> Result<u8, crate::Error> {
+        let query = neo4rs::query(
+            "MATCH (s:Student { name: $student, user_id: $user_id }) \
+            MATCH (t:Teacher { name: $advisor, user_id: $user_id }) \
+            MERGE (s)-[:PREFERS_ADVISOR]->(t)",
+        )
+        .param("student", self.student.clone())
+        .param("advisor", self.advisor.clone())
+        .param("user_id", user_id.into());
+
+        match graph.run(query).await {
+            Ok(_) => Ok(1),
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
+        }
+    }
+
+    /// List every request, ordered by the name of the student making it
+    pub async fn list<T: Into<String> + Send>(
+        graph: &neo4rs::Graph,
+        user_id: T,
+    ) -> Result<Arc<[Self]>, crate::Error> {
+        let query = neo4rs::query(
+            "MATCH (s:Student { user_id: $user_id })-[:PREFERS_ADVISOR]->(t:Teacher) \
+            RETURN s.name as student, t.name as advisor ORDER BY student, advisor",
+        )
+        .param("user_id", user_id.into());
+
+        match graph.execute(query).await {
+            Ok(mut result) => {
+                let mut preferences: Vec<Self> = Vec::new();
+                while let Ok(Some(row)) = result.next().await {
+                    match (
+                        row.get::<Arc<str>>("student"),
+                        row.get::<Arc<str>>("advisor"),
+                    ) {
+                        (Some(student), Some(advisor)) => {
+                            preferences.push(Self { student, advisor })
+                        }
+                        _ => return Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
+                    }
+                }
+                Ok(preferences.into())
+            }
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
+        }
+    }
+
+    /// Withdraw the request, removing the `[:PREFERS_ADVISOR]` relationship
+    pub async fn remove<T: Into<String> + Send>(
+        &self,
+        graph: &neo4rs::Graph,
+        user_id: T,
+    ) -> Result<u8, crate::Error> {
+        let query = neo4rs::query(
+            "MATCH (s:Student { name: $student, user_id: $user_id })-[r:PREFERS_ADVISOR]->(t:Teacher { name: $advisor, user_id: $user_id }) \
+            DELETE r",
+        )
+        .param("student", self.student.clone())
+        .param("advisor", self.advisor.clone())
+        .param("user_id", user_id.into());
+
+        match graph.run(query).await {
+            Ok(_) => Ok(1),
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
+        }
+    }
+}
@@ -1,21 +1,169 @@
-use crate::people::{Grade, Sex, Teacher};
+use super::person::{named_node, node_names};
+use crate::{
+    people::{Attributes, Grade, Teacher, GENDER},
+    Problem,
+};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
 /// Representation of a student
 #[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(from = "StudentForm")]
 pub struct Student {
     /// Student's name - should be in `First Last` format, but can be anything that distinguishes them from other students
     pub name: Arc<str>,
     /// Vector list of the student's teacher for the current academic school year
     pub teachers: Arc<[Teacher]>,
-    /// Student's grade represented with the [`Grade`] enum
+    /// Student's grade level, represented with the [`Grade`] struct
     pub grade: Grade,
-    /// Student's biological sex, represented by the [`Sex`] enum
-    /// Optional
-    pub sex: Option<Sex>,
-    /// People whom the student is not supposed to be placed with in an advisory
+    /// Student's demographic attributes, such as their [`GENDER`], by the name of the attribute
+    /// Each is optional
+    pub attributes: Attributes,
+    /// Students whom the student is not supposed to be placed with in an advisory
+    ///
+    /// Bans are stored by name, so a name shared by a teacher and a student is always read as the teacher and
+    /// kept in [`Student::banned_advisors`] instead
     pub banned_pairings: Arc<[Arc<str>]>,
+    /// Teachers who are not supposed to advise the student
+    #[serde(default)]
+    pub banned_advisors: Arc<[Arc<str>]>,
+    /// Students whom the student asked to be placed with, of which at least one should be in their advisory
+    #[serde(default)]
+    pub preferred_pairings: Arc<[Arc<str>]>,
+    /// Teachers whom the student asked to be advised by, of which at least one should advise their advisory
+    #[serde(default)]
+    pub preferred_advisors: Arc<[Arc<str>]>,
+}
+
+/// Form that a [`Student`] is sent in
+///
+/// Students used to be sent with a `sex` instead of attributes, which is read as their gender, and with
+/// teachers in their banned pairings, which are moved to their banned advisors when they are the student's
+/// own teachers
+#[derive(Deserialize)]
+struct StudentForm {
+    /// Student's name
+    name: Arc<str>,
+    /// Student's teachers
+    teachers: Arc<[Teacher]>,
+    /// Student's grade level
+    grade: Grade,
+    /// Student's demographic attributes
+    #[serde(default)]
+    attributes: Attributes,
+    /// Student's sex, from before attributes were added
+    #[serde(default)]
+    sex: Option<Arc<str>>,
+    /// Students whom the student is not supposed to be placed with
+    banned_pairings: Arc<[Arc<str>]>,
+    /// Teachers who are not supposed to advise the student
+    #[serde(default)]
+    banned_advisors: Arc<[Arc<str>]>,
+    /// Students whom the student asked to be placed with
+    #[serde(default)]
+    preferred_pairings: Arc<[Arc<str>]>,
+    /// Teachers whom the student asked to be advised by
+    #[serde(default)]
+    preferred_advisors: Arc<[Arc<str>]>,
+}
+
+impl From<StudentForm> for Student {
+    fn from(form: StudentForm) -> Self {
+        let mut attributes = form.attributes;
+        if let Some(sex) = form.sex.filter(|sex| !sex.is_empty()) {
+            attributes.entry(Arc::from(GENDER)).or_insert(sex);
+        }
+        let mut student = Self {
+            name: form.name,
+            teachers: form.teachers,
+            grade: form.grade,
+            attributes,
+            banned_pairings: form.banned_pairings,
+            banned_advisors: form.banned_advisors,
+            preferred_pairings: form.preferred_pairings,
+            preferred_advisors: form.preferred_advisors,
+        };
+        student.move_advisor_bans(&student.teachers.clone());
+        student
+    }
+}
+
+/// Read a student from a row returned by [`crate::DatabaseNode::get_nodes`], reporting every field that
+/// couldn't be read
+impl TryFrom<&neo4rs::Row> for Student {
+    type Error = Vec<Problem>;
+
+    fn try_from(row: &neo4rs::Row) -> Result<Self, Self::Error> {
+        let (person, name) = named_node(row, "students").map_err(|p| vec![p])?;
+        let grade = match person.get::<i64>("grade") {
+            Some(level) => Grade::try_from(level).map_err(|message| Problem::new("grade", message)),
+            None => Err(Problem::new("grade", "is missing")),
+        };
+        let mut attributes: Attributes = person
+            .get::<Vec<String>>("attribute_names")
+            .unwrap_or_default()
+            .into_iter()
+            .map(Arc::from)
+            .zip(
+                person
+                    .get::<Vec<String>>("attribute_values")
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(Arc::from),
+            )
+            .collect();
+        // students saved before attributes were added have their gender stored as `sex`
+        if let Some(sex) = person.get::<String>("sex").filter(|sex| !sex.is_empty()) {
+            attributes
+                .entry(Arc::from(GENDER))
+                .or_insert(Arc::from(sex));
+        }
+
+        let mut problems = Vec::new();
+        let mut read = |names: Result<Arc<[Arc<str>]>, Problem>| match names {
+            Ok(names) => Some(names),
+            Err(problem) => {
+                problems.push(problem.named(&name));
+                None
+            }
+        };
+        let teachers = read(node_names(row, "teachers", "teachers"));
+        // bans saved before they were typed are untyped `[:BANNED]` relationships, which are told apart by
+        // who they are with
+        let banned_pairings = read(node_names(row, "banned", "banned_pairings"));
+        let banned_advisors = read(node_names(row, "banned_advisors", "banned_advisors"));
+        let preferred_pairings = read(node_names(row, "preferred", "preferred_pairings"));
+        let preferred_advisors = read(node_names(row, "preferred_advisors", "preferred_advisors"));
+        let grade = grade.map_err(|p| problems.push(p.named(&name))).ok();
+
+        match (
+            grade,
+            teachers,
+            banned_pairings,
+            banned_advisors,
+            preferred_pairings,
+            preferred_advisors,
+        ) {
+            (
+                Some(grade),
+                Some(teachers),
+                Some(banned_pairings),
+                Some(banned_advisors),
+                Some(preferred_pairings),
+                Some(preferred_advisors),
+            ) => Ok(Self {
+                name,
+                teachers: teachers.iter().cloned().map(Teacher::new).collect(),
+                grade,
+                attributes,
+                banned_pairings,
+                banned_advisors,
+                preferred_pairings,
+                preferred_advisors,
+            }),
+            _ => Err(problems),
+        }
+    }
 }
 
 impl std::fmt::Display for Student {
@@ -25,20 +173,25 @@ impl std::fmt::Display for Student {
 }
 
 impl crate::Verify for Student {
-    /// Returns an [`axum::http::StatusCode`] type, so errors can be passed through to handlers
+    /// Finds a missing name, a missing list of teachers, and every problem with the teachers, naming the
+    /// student in each
     ///
     /// # Examples
     ///
     /// ```
-    /// # use advisory_backend_lib::{Verify, people::{Student, Teacher, Grade}};
-    /// fn func() -> Result<(), axum::http::StatusCode> {
-    ///     let teacher = Teacher { name: "Testing Name".to_owned() };
+    /// # use advisory_backend_lib::{Verify, people::{Attributes, Student, Teacher, Grade}};
+    /// # use std::sync::Arc;
+    /// fn func() -> Result<(), advisory_backend_lib::Error> {
+    ///     let teacher = Teacher::new("Testing Name");
     ///     let student = Student {
-    ///         name: "Testing Name".to_owned(),
-    ///         teachers: Arc::new([teacher]),
-    ///         grade: Grade::Freshman,
-    ///         sex: None,
-    ///         banned_pairings: Vec::new(),
+    ///         name: Arc::from("Testing Name"),
+    ///         teachers: Arc::from([teacher]),
+    ///         grade: Grade::FRESHMAN,
+    ///         attributes: Attributes::new(),
+    ///         banned_pairings: Arc::from([]),
+    ///         banned_advisors: Arc::from([]),
+    ///         preferred_pairings: Arc::from([]),
+    ///         preferred_advisors: Arc::from([]),
     ///     };
     ///     student.verify()?;
     ///     Ok(())
@@ -48,23 +201,33 @@ impl crate::Verify for Student {
     ///
     /// ```
     /// # use advisory_backend_lib::{Verify, people::{Student, Teacher}};
-    /// fn func() -> Result<(), axum::http::StatusCode> {
+    /// fn func() -> Result<(), advisory_backend_lib::Error> {
     ///     let student = Student::default();
     ///     student.verify()?;
     ///     Ok(())
     /// }
     /// assert_ne!(func(), Ok(()))
     /// ```
-    fn verify(&self) -> Result<(), axum::http::StatusCode> {
-        // Check if each teacher is valid
-        for i in self.teachers.iter() {
-            i.verify()?
+    fn problems(&self) -> Vec<crate::Problem> {
+        let mut problems = Vec::new();
+        if self.name.is_empty() {
+            problems.push(crate::Problem::new("name", "must not be empty"));
+        }
+        if self.teachers.is_empty() {
+            problems.push(crate::Problem::new(
+                "teachers",
+                "must have at least one teacher",
+            ));
         }
-        if self.name.is_empty() || self.teachers.is_empty() {
-            Err(axum::http::StatusCode::UNPROCESSABLE_ENTITY)
-        } else {
-            Ok(())
+        // Check if each teacher is valid
+        for (index, i) in self.teachers.iter().enumerate() {
+            problems.extend(
+                i.problems()
+                    .into_iter()
+                    .map(|p| p.within(&format!("teachers[{}]", index))),
+            );
         }
+        problems.into_iter().map(|p| p.named(&self.name)).collect()
     }
 }
 
@@ -73,14 +236,18 @@ impl Default for Student {
     /// Example
     ///
     /// ```
-    /// # use advisory_backend_lib::people::{Student, Teacher, Grade};
+    /// # use advisory_backend_lib::people::{Attributes, Student, Teacher, Grade};
+    /// # use std::sync::Arc;
     /// let default_student = Student::default();
     /// let student = Student {
-    ///     name: "".to_owned(),
-    ///     teachers: Vec::<Teacher>::new(),
-    ///     grade: Grade::Freshman,
-    ///     sex: None,
-    ///     banned_pairings: Vec::new(),
+    ///     name: Arc::from(""),
+    ///     teachers: Arc::from(Vec::<Teacher>::new()),
+    ///     grade: Grade::FRESHMAN,
+    ///     attributes: Attributes::new(),
+    ///     banned_pairings: Arc::from([]),
+    ///     banned_advisors: Arc::from([]),
+    ///     preferred_pairings: Arc::from([]),
+    ///     preferred_advisors: Arc::from([]),
     /// };
     /// assert_eq!(default_student, student);
     /// ```
@@ -88,13 +255,128 @@ impl Default for Student {
         Self {
             name: Arc::from(""),
             teachers: Arc::from([]),
-            grade: Grade::Freshman,
-            sex: None,
+            grade: Grade::FRESHMAN,
+            attributes: Attributes::new(),
             banned_pairings: Arc::from([]),
+            banned_advisors: Arc::from([]),
+            preferred_pairings: Arc::from([]),
+            preferred_advisors: Arc::from([]),
         }
     }
 }
 
+impl Student {
+    /// Split the student's attributes into lists of names and values, which is how they are stored in the
+    /// database
+    fn attribute_lists(&self) -> (Vec<String>, Vec<String>) {
+        self.attributes
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .unzip()
+    }
+
+    /// Move the teachers in the student's banned pairings to their banned advisors, since only banned
+    /// advisors keep teachers from advising the student
+    ///
+    /// A name that belongs to one of `teachers` is moved even if a student has the same name, so the teacher
+    /// is banned rather than the student
+    /// # Example
+    ///
+    /// ```
+    /// # use advisory_backend_lib::people::{Student, Teacher};
+    /// # use std::sync::Arc;
+    /// let mut student = Student {
+    ///     banned_pairings: Arc::from([Arc::from("Student 2"), Arc::from("Teacher 1")]),
+    ///     ..Default::default()
+    /// };
+    /// student.move_advisor_bans(&[Teacher::new("Teacher 1")]);
+    /// assert_eq!(student.banned_pairings[..], [Arc::from("Student 2")]);
+    /// assert_eq!(student.banned_advisors[..], [Arc::from("Teacher 1")]);
+    ///
+    /// // a teacher that shares a student's name is still moved
+    /// student.move_advisor_bans(&[Teacher::new("Student 2")]);
+    /// assert!(student.banned_pairings.is_empty());
+    /// ```
+    pub fn move_advisor_bans(&mut self, teachers: &[Teacher]) {
+        let (advisors, students): (Vec<Arc<str>>, Vec<Arc<str>>) = self
+            .banned_pairings
+            .iter()
+            .cloned()
+            .partition(|name| teachers.iter().any(|t| &t.name == name));
+        if advisors.is_empty() {
+            return;
+        }
+        let mut banned_advisors = self.banned_advisors.to_vec();
+        for name in advisors {
+            if !banned_advisors.contains(&name) {
+                banned_advisors.push(name);
+            }
+        }
+        self.banned_pairings = students.into();
+        self.banned_advisors = banned_advisors.into();
+    }
+
+    /// Names of the student's teachers, which are sent to the database as a list parameter
+    fn teacher_names(&self) -> Vec<String> {
+        self.teachers.iter().map(|t| t.name.to_string()).collect()
+    }
+
+    /// Query that adds one student, with every value read from a parameter so names are never part of the
+    /// query itself
+    fn add_node_query(no_duplicates: bool) -> &'static str {
+        match no_duplicates {
+            true => "WITH $teachers as teachers OPTIONAL MATCH (t:Teacher { user_id: $user_id }) WHERE t.name IN teachers MERGE (s:Student { name: $name, user_id: $user_id }) SET s.grade = $grade, s.attribute_names = $attribute_names, s.attribute_values = $attribute_values MERGE (s)<-[:TEACHES]-(t)",
+            false => "WITH $teachers as teachers OPTIONAL MATCH (t:Teacher { user_id: $user_id }) WHERE t.name IN teachers CREATE (s:Student { name: $name, grade: $grade, attribute_names: $attribute_names, attribute_values: $attribute_values, user_id: $user_id }) CREATE (s)<-[:TEACHES]-(t)",
+        }
+    }
+
+    /// Query that adds many students at once from the lists in a [`StudentLists`]
+    fn add_multiple_nodes_query(no_duplicates: bool) -> String {
+        let inside_query = match no_duplicates {
+            true => "MERGE (s:Student { name: student.name, user_id: $user_id }) SET s.grade = student.grade, s.attribute_names = student.attribute_names, s.attribute_values = student.attribute_values MERGE (s)<-[:TEACHES]-(t)",
+            false => "CREATE (s:Student { name: student.name, grade: student.grade, attribute_names: student.attribute_names, attribute_values: student.attribute_values, user_id: $user_id }) CREATE (s)<-[:TEACHES]-(t)",
+        };
+        // each student's values are sent as lists, and are read back by their index in the lists
+        format!(
+            "UNWIND range(0, size($names) - 1) AS index \
+            WITH {{ name: $names[index], grade: $grades[index], teachers: $teachers[index], attribute_names: $attribute_names[index], attribute_values: $attribute_values[index] }} AS student \
+            CALL {{ WITH student OPTIONAL MATCH (t:Teacher {{ user_id: $user_id }}) WHERE t.name IN student.teachers {} }}",
+            inside_query
+        )
+    }
+}
+
+/// Values of many students, sent to the database as one list parameter per field
+#[derive(Debug, Default, PartialEq, Eq)]
+struct StudentLists {
+    /// Name of each student
+    names: Vec<String>,
+    /// Grade of each student, as stored in the database
+    grades: Vec<i64>,
+    /// Names of each student's teachers
+    teachers: Vec<Vec<String>>,
+    /// Names of each student's attributes
+    attribute_names: Vec<Vec<String>>,
+    /// Values of each student's attributes, in the same order as their names
+    attribute_values: Vec<Vec<String>>,
+}
+
+impl StudentLists {
+    /// Collect the values of every student in order
+    fn new(nodes: &[Student]) -> Self {
+        let mut lists = Self::default();
+        for node in nodes {
+            let (attribute_names, attribute_values) = node.attribute_lists();
+            lists.names.push(node.name.to_string());
+            lists.grades.push(i64::from(&node.grade));
+            lists.teachers.push(node.teacher_names());
+            lists.attribute_names.push(attribute_names);
+            lists.attribute_values.push(attribute_values);
+        }
+        lists
+    }
+}
+
 #[async_trait::async_trait]
 impl crate::DatabaseNode for Student {
     async fn add_node<T: Into<String> + Send>(
@@ -102,37 +384,19 @@ impl crate::DatabaseNode for Student {
         graph: &neo4rs::Graph,
         user_id: T,
         no_duplicates: bool,
-    ) -> Result<u8, axum::http::StatusCode> {
-        let query_string = match no_duplicates {
-            true => 
-                "MERGE (s:Student { name: $name, grade: $grade, sex: $sex, user_id: $user_id }) MERGE (s)<-[:TEACHES]-(t)",
-            false => 
-                "CREATE (s:Student { name: $name, grade: $grade, sex: $sex, user_id: $user_id }) CREATE (s)<-[:TEACHES]-(t)",
-        };
-        // potential for sql injection by directly using the value from teachers
-        // that being said, it doesn't work otherwise
-        // maybe look for a way to sanitize inputs
-        let teachers = self
-            .teachers
-            .iter()
-            .map(|t| format!("\"{}\"", t.name))
-            .collect::<Vec<_>>()
-            .join(",");
-        let query = neo4rs::query(&format!("WITH [{}] as teachers OPTIONAL MATCH (t:Teacher {{ user_id: $user_id }}) WHERE t.name IN teachers {}", teachers, query_string))
+    ) -> Result<u8, crate::Error> {
+        let (attribute_names, attribute_values) = self.attribute_lists();
+        let query = neo4rs::query(Self::add_node_query(no_duplicates))
             .param("name", self.name.clone())
             .param("grade", i64::from(&self.grade))
-            .param(
-                "sex",
-                match &self.sex {
-                    Some(value) => value.to_string(),
-                    None => String::new(),
-                },
-            )
+            .param("teachers", self.teacher_names())
+            .param("attribute_names", attribute_names)
+            .param("attribute_values", attribute_values)
             .param("user_id", user_id.into());
 
         match graph.run(query).await {
             Ok(_) => Ok(1),
-            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR),
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
         }
     }
 
@@ -141,63 +405,19 @@ impl crate::DatabaseNode for Student {
         graph: &neo4rs::Graph,
         user_id: T,
         no_duplicates: bool,
-    ) -> Result<u8, axum::http::StatusCode> {
-        let inside_query = match no_duplicates {
-            true => "MERGE (s:Student { name: student.name, grade: student.grade, sex: student.sex, user_id: $user_id }) MERGE (s)<-[:TEACHES]-(t)",
-            false => 
-                "CREATE (s:Student { name: student.name, grade: student.grade, sex: student.sex, user_id: $user_id }) CREATE (s)<-[:TEACHES]-(t)"
-        };
-
-        let mut parameter_pairs: std::collections::HashMap<String, Arc<str>> =
-            std::collections::HashMap::new();
-        let parameter_list = nodes
-            .iter()
-            .map(|q| {
-                let key = random_string::generate(50, "abcdefghijklmnopqrstuvwxyz");
-                parameter_pairs.insert(key.clone() + "name", q.name.clone());
-                parameter_pairs.insert(
-                    key.clone() + "sex",
-                    match q.sex.clone() {
-                        Some(value) => value.into(),
-                        None => Arc::from(""),
-                    },
-                );
-                // potential for sql injection by directly using the value from teachers
-                // that being said, it doesn't work otherwise
-                // maybe look for a way to sanitize inputs
-                // (same as when adding single student)
-                let teachers = format!(
-                    "[{}]",
-                    q.teachers
-                        .iter()
-                        .map(|t| format!("\"{}\"", t.name))
-                        .collect::<Vec<_>>()
-                        .join(",")
-                );
-                format!(
-                    "{{ name: ${}name, grade: {}, sex: ${}sex, teachers: {} }}",
-                    key,
-                    i64::from(&q.grade),
-                    key,
-                    teachers
-                )
-            })
-            .collect::<Vec<_>>()
-            .join(",");
-        let query_string = format!(
-            "UNWIND [{}] as student CALL {{ WITH student OPTIONAL MATCH (t:Teacher {{ user_id: $user_id }}) WHERE t.name IN student.teachers {} }}",
-            parameter_list, inside_query
-        );
-        let mut query = neo4rs::query(&query_string).param("user_id", user_id.into());
-
-        // substitute values in
-        for (key, value) in parameter_pairs {
-            query = query.param(key.as_str(), value.clone());
-        }
+    ) -> Result<u8, crate::Error> {
+        let lists = StudentLists::new(nodes);
+        let query = neo4rs::query(&Self::add_multiple_nodes_query(no_duplicates))
+            .param("names", lists.names)
+            .param("grades", lists.grades)
+            .param("teachers", lists.teachers)
+            .param("attribute_names", lists.attribute_names)
+            .param("attribute_values", lists.attribute_values)
+            .param("user_id", user_id.into());
 
         match graph.run(query).await {
             Ok(_) => Ok(1),
-            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR),
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
         }
     }
 
@@ -205,85 +425,94 @@ impl crate::DatabaseNode for Student {
         &self,
         graph: &neo4rs::Graph,
         user_id: T,
-    ) -> Result<u8, axum::http::StatusCode> {
+    ) -> Result<u8, crate::Error> {
         let query = neo4rs::query(
-            "MATCH (s:Student { name: $name, grade: $grade, sex: $sex, user_id: $user_id }) DETACH DELETE s",
+            "MATCH (s:Student { name: $name, grade: $grade, user_id: $user_id }) DETACH DELETE s",
         )
         .param("name", self.name.clone())
-        .param::<Arc<str>>("grade", self.grade.clone().into())
-        .param::<Arc<str>>(
-            "sex",
-            match self.sex.clone() {
-                Some(value) => value.into(),
-                None => Arc::from(""),
-            },
-        )
+        .param("grade", i64::from(&self.grade))
         .param("user_id", user_id.into());
 
         match graph.run(query).await {
             Ok(_) => Ok(1),
-            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR),
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
         }
     }
 
     async fn clear_nodes<T: Into<String> + Send>(
         graph: &neo4rs::Graph,
         user_id: T,
-    ) -> Result<u8, axum::http::StatusCode> {
+    ) -> Result<u8, crate::Error> {
         let query = neo4rs::query("MATCH (s:Student { user_id: $user_id }) DETACH DELETE (s)")
             .param("user_id", user_id.into());
 
         match graph.run(query).await {
             Ok(_) => Ok(1),
-            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR),
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
         }
     }
 
     async fn get_nodes<T: Into<String> + Send>(
         graph: &neo4rs::Graph,
         user_id: T,
-    ) -> Result<Arc<[Self]>, axum::http::StatusCode> {
-        let query = neo4rs::query("MATCH (s:Student { user_id: $user_id }) OPTIONAL MATCH (s)<-[:TEACHES]-(t:Teacher) OPTIONAL MATCH (s)-[:BANNED]-(b) RETURN distinct(s) as students, collect(t) as teachers, collect(b) as banned")
+    ) -> Result<Arc<[Self]>, crate::Error> {
+        let query = neo4rs::query("MATCH (s:Student { user_id: $user_id }) OPTIONAL MATCH (s)<-[:TEACHES]-(t:Teacher) OPTIONAL MATCH (s)-[:BANNED_STUDENT|BANNED]-(b:Student) OPTIONAL MATCH (s)-[:BANNED_ADVISOR|BANNED]-(ba:Teacher) OPTIONAL MATCH (s)-[:PREFERS]->(f:Student) OPTIONAL MATCH (s)-[:PREFERS_ADVISOR]->(fa:Teacher) RETURN distinct(s) as students, collect(distinct t) as teachers, collect(distinct b) as banned, collect(distinct ba) as banned_advisors, collect(distinct f) as preferred, collect(distinct fa) as preferred_advisors ORDER BY students.name")
             .param("user_id", user_id.into());
 
         match graph.execute(query).await {
             Ok(mut result) => {
                 let mut students: Vec<Self> = Vec::new();
+                let mut problems = Vec::new();
+                let mut index = 0;
                 while let Ok(Some(row)) = result.next().await {
-                    let person: neo4rs::Node = row.get("students").unwrap();
-                    let name: Arc<str> = person.get("name").unwrap();
-                    let grade: Grade = person.get::<i64>("grade").unwrap().into();
-                    let sex: Option<Sex> = match person.get::<String>("sex") {
-                        Some(v) if v != "" => Some(Sex::from(v)),
-                        _ => None,
-                    };
-
-                    let banned_pairings = row
-                        .get::<Vec<neo4rs::Node>>("banned")
-                        .unwrap()
-                        .iter()
-                        .map(|b| b.get::<Arc<str>>("name").unwrap())
-                        .collect::<Arc<[_]>>();
-                    let teachers = row
-                        .get::<Vec<neo4rs::Node>>("teachers")
-                        .unwrap()
-                        .iter()
-                        .map(|t| Teacher::new(
-                            t.get::<Arc<str>>("name").unwrap(),
-                        ))
-                        .collect::<Arc<[_]>>();
-
-                    students.push(Self {
-                        name,
-                        teachers,
-                        grade,
-                        sex,
-                        banned_pairings,
-                    })
+                    match Self::try_from(&row) {
+                        Ok(student) => students.push(student),
+                        Err(found) => problems.extend(found.into_iter().map(|p| p.at(index))),
+                    }
+                    index += 1;
                 }
+                crate::Error::check_records(problems)?;
                 Ok(students.into())
             }
-            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR),
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A teacher name that would end the query early if it were written into the query text
+    const TEACHER: &str = "Teacher\" }) DETACH DELETE (n) //";
+
+    fn student() -> Student {
+        Student {
+            name: Arc::from("Student 1"),
+            teachers: Arc::from([Teacher::new(TEACHER)]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn add_node_sends_teacher_names_as_a_list() {
+        for no_duplicates in [true, false] {
+            let query = Student::add_node_query(no_duplicates);
+            assert!(!query.contains(TEACHER));
+            assert!(query.contains("t.name IN teachers"));
+        }
+        assert_eq!(student().teacher_names(), vec![TEACHER.to_string()]);
+    }
+
+    #[test]
+    fn add_multiple_nodes_sends_teacher_names_as_a_list() {
+        for no_duplicates in [true, false] {
+            let query = Student::add_multiple_nodes_query(no_duplicates);
+            assert!(!query.contains(TEACHER));
+            assert!(query.contains("teachers: $teachers[index]"));
         }
+        let lists = StudentLists::new(&[student(), student()]);
+        assert_eq!(lists.names, vec!["Student 1", "Student 1"]);
+        assert_eq!(lists.teachers, vec![vec![TEACHER.to_string()]; 2]);
     }
 }
@@ -13,17 +13,58 @@ impl Teacher {
     pub fn new<T: Into<Arc<str>>>(name: T) -> Self {
         Self { name: name.into() }
     }
+
+    /// Get every pair of teachers that are banned from advising the same advisory, which are linked by a
+    /// `[:BANNED_COADVISOR]` relationship
+    ///
+    /// Teachers banned from each other before bans were typed are linked by a `[:BANNED]` relationship
+    /// instead
+    pub async fn get_coadvisor_bans<T: Into<String> + Send>(
+        graph: &neo4rs::Graph,
+        user_id: T,
+    ) -> Result<Arc<[(Arc<str>, Arc<str>)]>, crate::Error> {
+        let query = neo4rs::query(
+            "MATCH (t1:Teacher { user_id: $user_id })-[:BANNED_COADVISOR|BANNED]-(t2:Teacher { user_id: $user_id }) \
+            WHERE t1.name < t2.name \
+            RETURN DISTINCT t1.name as first, t2.name as second",
+        )
+        .param("user_id", user_id.into());
+
+        match graph.execute(query).await {
+            Ok(mut result) => {
+                let mut bans: Vec<(Arc<str>, Arc<str>)> = Vec::new();
+                while let Ok(Some(row)) = result.next().await {
+                    match (row.get::<Arc<str>>("first"), row.get::<Arc<str>>("second")) {
+                        (Some(first), Some(second)) => bans.push((first, second)),
+                        _ => return Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
+                    }
+                }
+                Ok(bans.into())
+            }
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
+        }
+    }
+}
+
+/// Read a teacher from a row returned by [`crate::DatabaseNode::get_nodes`]
+impl TryFrom<&neo4rs::Row> for Teacher {
+    type Error = Vec<crate::Problem>;
+
+    fn try_from(row: &neo4rs::Row) -> Result<Self, Self::Error> {
+        let (_, name) = super::person::named_node(row, "teachers").map_err(|p| vec![p])?;
+        Ok(Self::new(name))
+    }
 }
 
 impl crate::Verify for Teacher {
-    /// Returns an [`axum::http::StatusCode`] type, so errors can be passed through to handlers
+    /// Finds a missing name
     ///
     /// # Examples
     ///
     /// ```
     /// # use advisory_backend_lib::{Verify, people::{Teacher}};
-    /// fn func() -> Result<(), axum::http::StatusCode> {
-    ///     let teacher = Teacher { name: "Testing Name".to_owned() };
+    /// fn func() -> Result<(), advisory_backend_lib::Error> {
+    ///     let teacher = Teacher::new("Testing Name");
     ///     teacher.verify()?;
     ///     Ok(())
     /// }
@@ -32,19 +73,19 @@ impl crate::Verify for Teacher {
     ///
     /// ```
     /// # use advisory_backend_lib::{Verify, people::{Student, Teacher}};
-    /// fn func() -> Result<(), axum::http::StatusCode> {
-    ///     let teacher = Teacher { name: "".to_owned() };
+    /// fn func() -> Result<(), advisory_backend_lib::Error> {
+    ///     let teacher = Teacher::new("");
     ///     teacher.verify()?;
     ///     Ok(())
     /// }
     /// assert_ne!(func(), Ok(()))
     /// ```
-    fn verify(&self) -> Result<(), axum::http::StatusCode> {
+    fn problems(&self) -> Vec<crate::Problem> {
+        let mut problems = Vec::new();
         if self.name.is_empty() {
-            Err(axum::http::StatusCode::UNPROCESSABLE_ENTITY)
-        } else {
-            Ok(())
+            problems.push(crate::Problem::new("name", "must not be empty"));
         }
+        problems
     }
 }
 
@@ -61,7 +102,7 @@ impl crate::DatabaseNode for Teacher {
         graph: &neo4rs::Graph,
         user_id: T,
         no_duplicates: bool,
-    ) -> Result<u8, axum::http::StatusCode> {
+    ) -> Result<u8, crate::Error> {
         let query = match no_duplicates {
             true => neo4rs::query("MERGE (t:Teacher { name: $name, user_id: $user_id })"),
             false => neo4rs::query("CREATE (t:Teacher { name: $name, user_id: $user_id })"),
@@ -71,7 +112,7 @@ impl crate::DatabaseNode for Teacher {
 
         match graph.run(query).await {
             Ok(_) => Ok(1),
-            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR),
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
         }
     }
 
@@ -80,40 +121,23 @@ impl crate::DatabaseNode for Teacher {
         graph: &neo4rs::Graph,
         user_id: T,
         no_duplicates: bool,
-    ) -> Result<u8, axum::http::StatusCode> {
+    ) -> Result<u8, crate::Error> {
         let inside_query = match no_duplicates {
-            true => "MERGE (t:Teacher { name: teacher.name, user_id: $user_id })",
-            false => "CREATE (t:Teacher { name: teacher.name, user_id: $user_id })",
+            true => "MERGE (t:Teacher { name: name, user_id: $user_id })",
+            false => "CREATE (t:Teacher { name: name, user_id: $user_id })",
         };
 
-        let mut parameter_pairs: std::collections::HashMap<String, Arc<str>> =
-            std::collections::HashMap::new();
-        let parameter_list = nodes
-            .iter()
-            .map(|q| {
-                let key = random_string::generate(50, "abcdefghijklmnopqrstuvwxyz");
-                parameter_pairs.insert(key.clone(), q.name.clone());
-                format!("{{ name: ${} }}", key)
-            })
-            .collect::<Vec<_>>()
-            .join(",");
-        let mut query = neo4rs::query(
-            format!(
-                "UNWIND [{}] as teacher CALL {{ WITH teacher {} }}",
-                parameter_list, inside_query
-            )
-            .as_str(),
-        )
+        let names: Vec<String> = nodes.iter().map(|n| n.name.to_string()).collect();
+        let query = neo4rs::query(&format!(
+            "UNWIND $names AS name CALL {{ WITH name {} }}",
+            inside_query
+        ))
+        .param("names", names)
         .param("user_id", user_id.into());
 
-        // substitute values in
-        for (key, value) in parameter_pairs {
-            query = query.param(&key, value);
-        }
-
         match graph.run(query).await {
             Ok(_) => Ok(1),
-            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR),
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
         }
     }
 
@@ -121,7 +145,7 @@ impl crate::DatabaseNode for Teacher {
         &self,
         graph: &neo4rs::Graph,
         user_id: T,
-    ) -> Result<u8, axum::http::StatusCode> {
+    ) -> Result<u8, crate::Error> {
         let query =
             neo4rs::query("MATCH (t:Teacher { name: $name, user_id: $user_id }) DETACH DELETE t")
                 .param("name", self.name.clone())
@@ -129,27 +153,27 @@ impl crate::DatabaseNode for Teacher {
 
         match graph.run(query).await {
             Ok(_) => Ok(1),
-            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR),
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
         }
     }
 
     async fn clear_nodes<T: Into<String> + Send>(
         graph: &neo4rs::Graph,
         user_id: T,
-    ) -> Result<u8, axum::http::StatusCode> {
+    ) -> Result<u8, crate::Error> {
         let query = neo4rs::query("MATCH (t:Teacher { user_id: $user_id }) DETACH DELETE (t)")
             .param("user_id", user_id.into());
 
         match graph.run(query).await {
             Ok(_) => Ok(1),
-            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR),
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
         }
     }
 
     async fn get_nodes<T: Into<String> + Send>(
         graph: &neo4rs::Graph,
         user_id: T,
-    ) -> Result<Arc<[Self]>, axum::http::StatusCode> {
+    ) -> Result<Arc<[Self]>, crate::Error> {
         let query =
             neo4rs::query("MATCH (t:Teacher { user_id: $user_id }) RETURN distinct(t) as teachers")
                 .param("user_id", user_id.into());
@@ -157,14 +181,19 @@ impl crate::DatabaseNode for Teacher {
         match graph.execute(query).await {
             Ok(mut result) => {
                 let mut people: Vec<Self> = Vec::new();
+                let mut problems = Vec::new();
+                let mut index = 0;
                 while let Ok(Some(row)) = result.next().await {
-                    let person: neo4rs::Node = row.get("teachers").unwrap();
-                    let name: Arc<str> = person.get("name").unwrap();
-                    people.push(Self::new(name))
+                    match Self::try_from(&row) {
+                        Ok(teacher) => people.push(teacher),
+                        Err(found) => problems.extend(found.into_iter().map(|p| p.at(index))),
+                    }
+                    index += 1;
                 }
+                crate::Error::check_records(problems)?;
                 Ok(people.into())
             }
-            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR),
+            Err(_) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()),
         }
     }
 }
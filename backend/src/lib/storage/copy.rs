@@ -0,0 +1,67 @@
+use super::Repository;
+use crate::{people::Person, Error};
+use std::sync::Arc;
+
+/// Copy everything stored for an account from one repository into another, such as when moving an account
+/// from the [`neo4rs`] database to a [`super::SqliteRepository`]
+///
+/// Everything already stored for the account in `to` is kept, other than its grade levels, which are replaced
+/// by the ones in `from`. Copying twice leaves a single copy, and saved organizations keep their ids and
+/// creation times
+///
+/// # Example
+///
+/// ```
+/// # use advisory_backend_lib::{people::Teacher, storage::{copy_account, MemoryRepository, Repository, SqliteRepository}};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), advisory_backend_lib::Error> {
+/// let from = MemoryRepository::new();
+/// from.add_teachers("user", &[Teacher::new("Teacher")]).await?;
+///
+/// let to = SqliteRepository::open(":memory:")?;
+/// copy_account(&from, &to, "user").await?;
+/// assert_eq!(to.get_teachers("user").await?[..], [Teacher::new("Teacher")]);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn copy_account(
+    from: &dyn Repository,
+    to: &dyn Repository,
+    user_id: &str,
+) -> Result<(), Error> {
+    to.set_grade_levels(user_id, &from.get_grade_levels(user_id).await?)
+        .await?;
+
+    // people come before their relationships, which are only stored between stored people
+    let teachers = from.get_teachers(user_id).await?;
+    to.add_teachers(user_id, &teachers).await?;
+    let students = from.get_students(user_id).await?;
+    to.add_students(user_id, &students).await?;
+
+    let named = |name: &Arc<str>| Person {
+        name: name.clone(),
+        banned_pairings: Arc::new([]),
+    };
+    for person in from.get_people(user_id).await?.iter() {
+        for banned in person.banned_pairings.iter() {
+            to.ban_pair(user_id, [named(&person.name), named(banned)])
+                .await?;
+        }
+    }
+    for preference in from.get_preferences(user_id).await?.iter() {
+        to.add_preference(user_id, preference).await?;
+    }
+    for preference in from.get_advisor_preferences(user_id).await?.iter() {
+        to.add_advisor_preference(user_id, preference).await?;
+    }
+    for group in from.get_groups(user_id).await?.iter() {
+        to.set_group(user_id, group).await?;
+    }
+
+    for version in from.list_organizations(user_id).await?.iter() {
+        let saved = from.get_organization(user_id, &version.id).await?;
+        to.restore_organization(user_id, &saved.info, &saved.settings, &saved.organization)
+            .await?;
+    }
+    Ok(())
+}
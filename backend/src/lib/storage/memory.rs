@@ -0,0 +1,503 @@
+use super::Repository;
+use crate::{
+    advisories::{Organization, Placement, SavedOrganization, Settings, VersionInfo},
+    people::{AdvisorPreference, GradeLevels, Group, Person, Preference, Student, Teacher},
+    Error,
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+/// Organization saved in memory
+struct StoredOrganization {
+    /// Name, id and creation time of the saved organization
+    info: VersionInfo,
+    /// Settings the organization was generated with
+    settings: Settings,
+    /// Names of the advisors and students of each advisory, so students are filled in when fetched
+    placements: Vec<Placement>,
+}
+
+/// Everything stored for one account
+#[derive(Default)]
+struct Account {
+    /// Stored teachers
+    teachers: Vec<Teacher>,
+    /// Stored students, with only the teachers they are linked to, since their other relationships are
+    /// stored separately
+    students: Vec<Student>,
+    /// Names of each pair of people banned from being together
+    bans: Vec<(Arc<str>, Arc<str>)>,
+    /// Requests to be placed with a peer
+    preferences: Vec<Preference>,
+    /// Requests to be advised by a teacher
+    advisor_preferences: Vec<AdvisorPreference>,
+    /// Groups of students that are placed together
+    groups: Vec<Group>,
+    /// Grade levels, if any were set
+    grade_levels: Option<GradeLevels>,
+    /// Saved organizations, oldest first
+    organizations: Vec<StoredOrganization>,
+}
+
+impl Account {
+    /// Whether a teacher with the name is stored
+    fn is_teacher(&self, name: &str) -> bool {
+        self.teachers.iter().any(|t| &*t.name == name)
+    }
+
+    /// Whether a student with the name is stored
+    fn is_student(&self, name: &str) -> bool {
+        self.students.iter().any(|s| &*s.name == name)
+    }
+
+    /// Names of everyone that a person is banned from being with
+    fn banned_from<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Arc<str>> {
+        self.bans.iter().filter_map(move |(first, second)| {
+            if &**first == name {
+                Some(second)
+            } else if &**second == name {
+                Some(first)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Every student, ordered by name, along with their bans and preferences
+    ///
+    /// A ban with a name that belongs to both a teacher and a student is read as a ban of the teacher
+    fn students(&self) -> Arc<[Student]> {
+        let mut students: Vec<Student> = self
+            .students
+            .iter()
+            .map(|s| Student {
+                banned_pairings: self
+                    .banned_from(&s.name)
+                    .filter(|n| self.is_student(n) && !self.is_teacher(n))
+                    .cloned()
+                    .collect(),
+                banned_advisors: self
+                    .banned_from(&s.name)
+                    .filter(|n| self.is_teacher(n))
+                    .cloned()
+                    .collect(),
+                preferred_pairings: self
+                    .preferences
+                    .iter()
+                    .filter(|p| p.student == s.name)
+                    .map(|p| p.preferred.clone())
+                    .collect(),
+                preferred_advisors: self
+                    .advisor_preferences
+                    .iter()
+                    .filter(|p| p.student == s.name)
+                    .map(|p| p.advisor.clone())
+                    .collect(),
+                ..s.clone()
+            })
+            .collect();
+        students.sort_by(|a, b| a.name.cmp(&b.name));
+        students.into()
+    }
+
+    /// Find a saved organization by its id
+    fn organization(&mut self, id: &str) -> Option<&mut StoredOrganization> {
+        self.organizations.iter_mut().find(|o| &*o.info.id == id)
+    }
+}
+
+/// Storage kept in memory, which is lost when the server stops
+///
+/// Behaves the same as the database, so the server and its tests can run without one
+///
+/// # Example
+///
+/// ```
+/// # use advisory_backend_lib::{people::{Student, Teacher}, storage::{MemoryRepository, Repository}};
+/// # use std::sync::Arc;
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), advisory_backend_lib::Error> {
+/// let repository = MemoryRepository::new();
+/// let teacher = Teacher::new("Teacher");
+/// repository.add_teachers("user", &[teacher.clone()]).await?;
+/// let student = Student {
+///     name: Arc::from("Student"),
+///     teachers: Arc::from([teacher.clone()]),
+///     ..Default::default()
+/// };
+/// repository.add_students("user", &[student.clone()]).await?;
+/// repository.ban_pair("user", [teacher.clone().into(), student.into()]).await?;
+///
+/// let students = repository.get_students("user").await?;
+/// assert_eq!(students[0].teachers[..], [teacher]);
+/// assert_eq!(students[0].banned_advisors[..], [Arc::from("Teacher")]);
+/// assert!(repository.get_students("someone else").await?.is_empty());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct MemoryRepository {
+    /// Everything stored for each account, by user id
+    accounts: Mutex<HashMap<String, Account>>,
+}
+
+impl MemoryRepository {
+    /// Creates an empty repository
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run a function on an account's storage, creating it if nothing was stored for the account yet
+    fn with_account<R>(&self, user_id: &str, f: impl FnOnce(&mut Account) -> R) -> R {
+        let mut accounts = self.accounts.lock().unwrap_or_else(PoisonError::into_inner);
+        f(accounts.entry(user_id.to_owned()).or_default())
+    }
+
+    /// Run a function that only reads an account's storage, which is empty if nothing was stored for the
+    /// account yet, without creating it
+    fn read_account<R>(&self, user_id: &str, f: impl FnOnce(&Account) -> R) -> R {
+        let accounts = self.accounts.lock().unwrap_or_else(PoisonError::into_inner);
+        match accounts.get(user_id) {
+            Some(account) => f(account),
+            None => f(&Account::default()),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Repository for MemoryRepository {
+    async fn get_people(&self, user_id: &str) -> Result<Arc<[Person]>, Error> {
+        Ok(self.read_account(user_id, |account| {
+            let names = account.teachers.iter().map(|t| &t.name);
+            let mut people: Vec<Person> = names
+                .chain(account.students.iter().map(|s| &s.name))
+                .map(|name| Person {
+                    name: name.clone(),
+                    banned_pairings: account.banned_from(name).cloned().collect(),
+                })
+                .collect();
+            people.sort_by(|a, b| a.name.cmp(&b.name));
+            people.into()
+        }))
+    }
+
+    async fn clear_people(&self, user_id: &str) -> Result<u8, Error> {
+        self.with_account(user_id, |account| {
+            account.teachers.clear();
+            account.students.clear();
+            account.bans.clear();
+            account.preferences.clear();
+            account.advisor_preferences.clear();
+            account.groups.clear();
+        });
+        Ok(1)
+    }
+
+    async fn add_teachers(&self, user_id: &str, teachers: &[Teacher]) -> Result<u8, Error> {
+        self.with_account(user_id, |account| {
+            for teacher in teachers {
+                if !account.is_teacher(&teacher.name) {
+                    account.teachers.push(teacher.clone());
+                }
+            }
+        });
+        Ok(1)
+    }
+
+    async fn get_teachers(&self, user_id: &str) -> Result<Arc<[Teacher]>, Error> {
+        Ok(self.read_account(user_id, |account| account.teachers.as_slice().into()))
+    }
+
+    async fn add_students(&self, user_id: &str, students: &[Student]) -> Result<u8, Error> {
+        self.with_account(user_id, |account| {
+            for student in students {
+                let teachers: Vec<Teacher> = student
+                    .teachers
+                    .iter()
+                    .filter(|t| account.is_teacher(&t.name))
+                    .cloned()
+                    .collect();
+                let stored = account.students.iter_mut().find(|s| s.name == student.name);
+                match stored {
+                    Some(stored) => {
+                        let mut linked = stored.teachers.to_vec();
+                        for teacher in teachers {
+                            if !linked.contains(&teacher) {
+                                linked.push(teacher);
+                            }
+                        }
+                        stored.teachers = linked.into();
+                        stored.grade = student.grade;
+                        stored.attributes = student.attributes.clone();
+                    }
+                    None => account.students.push(Student {
+                        name: student.name.clone(),
+                        teachers: teachers.into(),
+                        grade: student.grade,
+                        attributes: student.attributes.clone(),
+                        ..Default::default()
+                    }),
+                }
+            }
+        });
+        Ok(1)
+    }
+
+    async fn get_students(&self, user_id: &str) -> Result<Arc<[Student]>, Error> {
+        Ok(self.read_account(user_id, |account| account.students()))
+    }
+
+    async fn ban_pair(&self, user_id: &str, pair: [Person; 2]) -> Result<u8, Error> {
+        self.with_account(user_id, |account| {
+            let stored = |name: &str| account.is_student(name) || account.is_teacher(name);
+            Person::check_stored(&pair, [stored(&pair[0].name), stored(&pair[1].name)])?;
+            let [first, second] = pair;
+            let banned = account.banned_from(&first.name).any(|n| n == &second.name);
+            if first.name != second.name && !banned {
+                account.bans.push((first.name, second.name));
+            }
+            Ok(1)
+        })
+    }
+
+    async fn get_coadvisor_bans(
+        &self,
+        user_id: &str,
+    ) -> Result<Arc<[(Arc<str>, Arc<str>)]>, Error> {
+        Ok(self.read_account(user_id, |account| {
+            let mut bans: Vec<(Arc<str>, Arc<str>)> = account
+                .bans
+                .iter()
+                .filter(|(first, second)| account.is_teacher(first) && account.is_teacher(second))
+                .map(|(first, second)| match first < second {
+                    true => (first.clone(), second.clone()),
+                    false => (second.clone(), first.clone()),
+                })
+                .collect();
+            bans.sort();
+            bans.into()
+        }))
+    }
+
+    async fn add_preference(&self, user_id: &str, preference: &Preference) -> Result<u8, Error> {
+        self.with_account(user_id, |account| {
+            if account.is_student(&preference.student)
+                && account.is_student(&preference.preferred)
+                && !account.preferences.contains(preference)
+            {
+                account.preferences.push(preference.clone());
+            }
+        });
+        Ok(1)
+    }
+
+    async fn get_preferences(&self, user_id: &str) -> Result<Arc<[Preference]>, Error> {
+        Ok(self.read_account(user_id, |account| {
+            let mut preferences = account.preferences.clone();
+            preferences.sort_by(|a, b| (&a.student, &a.preferred).cmp(&(&b.student, &b.preferred)));
+            preferences.into()
+        }))
+    }
+
+    async fn remove_preference(&self, user_id: &str, preference: &Preference) -> Result<u8, Error> {
+        self.with_account(user_id, |account| {
+            account.preferences.retain(|p| p != preference)
+        });
+        Ok(1)
+    }
+
+    async fn add_advisor_preference(
+        &self,
+        user_id: &str,
+        preference: &AdvisorPreference,
+    ) -> Result<u8, Error> {
+        self.with_account(user_id, |account| {
+            if account.is_student(&preference.student)
+                && account.is_teacher(&preference.advisor)
+                && !account.advisor_preferences.contains(preference)
+            {
+                account.advisor_preferences.push(preference.clone());
+            }
+        });
+        Ok(1)
+    }
+
+    async fn get_advisor_preferences(
+        &self,
+        user_id: &str,
+    ) -> Result<Arc<[AdvisorPreference]>, Error> {
+        Ok(self.read_account(user_id, |account| {
+            let mut preferences = account.advisor_preferences.clone();
+            preferences.sort_by(|a, b| (&a.student, &a.advisor).cmp(&(&b.student, &b.advisor)));
+            preferences.into()
+        }))
+    }
+
+    async fn remove_advisor_preference(
+        &self,
+        user_id: &str,
+        preference: &AdvisorPreference,
+    ) -> Result<u8, Error> {
+        self.with_account(user_id, |account| {
+            account.advisor_preferences.retain(|p| p != preference)
+        });
+        Ok(1)
+    }
+
+    async fn set_group(&self, user_id: &str, group: &Group) -> Result<u8, Error> {
+        self.with_account(user_id, |account| {
+            let group = Group {
+                name: group.name.clone(),
+                members: group
+                    .members
+                    .iter()
+                    .filter(|m| account.is_student(m))
+                    .cloned()
+                    .collect(),
+            };
+            account.groups.retain(|g| g.name != group.name);
+            account.groups.push(group);
+        });
+        Ok(1)
+    }
+
+    async fn get_groups(&self, user_id: &str) -> Result<Arc<[Group]>, Error> {
+        Ok(self.read_account(user_id, |account| {
+            let mut groups: Vec<Group> = account
+                .groups
+                .iter()
+                .map(|g| {
+                    let mut members = g.members.to_vec();
+                    members.sort();
+                    Group {
+                        name: g.name.clone(),
+                        members: members.into(),
+                    }
+                })
+                .collect();
+            groups.sort_by(|a, b| a.name.cmp(&b.name));
+            groups.into()
+        }))
+    }
+
+    async fn remove_group(&self, user_id: &str, name: &str) -> Result<u8, Error> {
+        self.with_account(user_id, |account| {
+            account.groups.retain(|g| &*g.name != name)
+        });
+        Ok(1)
+    }
+
+    async fn get_grade_levels(&self, user_id: &str) -> Result<GradeLevels, Error> {
+        Ok(self.read_account(user_id, |account| {
+            account.grade_levels.clone().unwrap_or_default()
+        }))
+    }
+
+    async fn set_grade_levels(&self, user_id: &str, levels: &GradeLevels) -> Result<u8, Error> {
+        self.with_account(user_id, |account| {
+            account.grade_levels = Some(levels.clone())
+        });
+        Ok(1)
+    }
+
+    async fn save_organization(
+        &self,
+        user_id: &str,
+        name: Arc<str>,
+        settings: &Settings,
+        organization: &Organization,
+    ) -> Result<VersionInfo, Error> {
+        let info = VersionInfo::new(name);
+        self.restore_organization(user_id, &info, settings, organization)
+            .await?;
+        Ok(info)
+    }
+
+    async fn restore_organization(
+        &self,
+        user_id: &str,
+        info: &VersionInfo,
+        settings: &Settings,
+        organization: &Organization,
+    ) -> Result<u8, Error> {
+        self.with_account(user_id, |account| {
+            account.organizations.retain(|o| o.info.id != info.id);
+            account.organizations.push(StoredOrganization {
+                info: info.clone(),
+                settings: settings.clone(),
+                placements: organization.placements(),
+            })
+        });
+        Ok(1)
+    }
+
+    async fn list_organizations(&self, user_id: &str) -> Result<Arc<[VersionInfo]>, Error> {
+        Ok(self.read_account(user_id, |account| {
+            let mut versions: Vec<VersionInfo> = account
+                .organizations
+                .iter()
+                .map(|o| o.info.clone())
+                .collect();
+            versions.sort_by(|a, b| b.created.cmp(&a.created));
+            versions.into()
+        }))
+    }
+
+    async fn get_organization(&self, user_id: &str, id: &str) -> Result<SavedOrganization, Error> {
+        self.read_account(user_id, |account| {
+            let students = account.students();
+            let stored = account
+                .organizations
+                .iter()
+                .find(|o| &*o.info.id == id)
+                .ok_or(axum::http::StatusCode::NOT_FOUND)?;
+            Ok(SavedOrganization {
+                info: stored.info.clone(),
+                settings: stored.settings.clone(),
+                organization: Organization::rebuild(
+                    &stored.settings,
+                    &stored.placements,
+                    &students,
+                ),
+            })
+        })
+    }
+
+    async fn update_organization(
+        &self,
+        user_id: &str,
+        id: &str,
+        organization: &Organization,
+    ) -> Result<u8, Error> {
+        self.with_account(user_id, |account| {
+            if let Some(stored) = account.organization(id) {
+                stored.placements = organization.placements();
+            }
+        });
+        Ok(1)
+    }
+
+    async fn rename_organization(
+        &self,
+        user_id: &str,
+        id: &str,
+        name: Arc<str>,
+    ) -> Result<VersionInfo, Error> {
+        self.with_account(user_id, |account| {
+            let stored = account
+                .organization(id)
+                .ok_or(axum::http::StatusCode::NOT_FOUND)?;
+            stored.info.name = name;
+            Ok(stored.info.clone())
+        })
+    }
+
+    async fn delete_organization(&self, user_id: &str, id: &str) -> Result<u8, Error> {
+        self.with_account(user_id, |account| {
+            account.organizations.retain(|o| &*o.info.id != id)
+        });
+        Ok(1)
+    }
+}
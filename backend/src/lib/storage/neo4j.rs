@@ -0,0 +1,151 @@
+use super::Repository;
+use crate::{
+    advisories::{Organization, SavedOrganization, Settings, VersionInfo},
+    people::{AdvisorPreference, GradeLevels, Group, Person, Preference, Student, Teacher},
+    DatabaseNode, Error,
+};
+use std::sync::Arc;
+
+/// Storage in the [`neo4rs`] database, with each account's nodes marked by a `user_id` property
+#[async_trait::async_trait]
+impl Repository for neo4rs::Graph {
+    async fn get_people(&self, user_id: &str) -> Result<Arc<[Person]>, Error> {
+        Person::get_nodes(self, user_id).await
+    }
+
+    async fn clear_people(&self, user_id: &str) -> Result<u8, Error> {
+        Person::clear_nodes(self, user_id).await
+    }
+
+    async fn add_teachers(&self, user_id: &str, teachers: &[Teacher]) -> Result<u8, Error> {
+        Teacher::add_multiple_nodes(teachers, self, user_id, true).await
+    }
+
+    async fn get_teachers(&self, user_id: &str) -> Result<Arc<[Teacher]>, Error> {
+        Teacher::get_nodes(self, user_id).await
+    }
+
+    async fn add_students(&self, user_id: &str, students: &[Student]) -> Result<u8, Error> {
+        Student::add_multiple_nodes(students, self, user_id, true).await
+    }
+
+    async fn get_students(&self, user_id: &str) -> Result<Arc<[Student]>, Error> {
+        Student::get_nodes(self, user_id).await
+    }
+
+    async fn ban_pair(&self, user_id: &str, pair: [Person; 2]) -> Result<u8, Error> {
+        Person::ban_pair(pair, self, user_id, true).await
+    }
+
+    async fn get_coadvisor_bans(
+        &self,
+        user_id: &str,
+    ) -> Result<Arc<[(Arc<str>, Arc<str>)]>, Error> {
+        Teacher::get_coadvisor_bans(self, user_id).await
+    }
+
+    async fn add_preference(&self, user_id: &str, preference: &Preference) -> Result<u8, Error> {
+        preference.add(self, user_id).await
+    }
+
+    async fn get_preferences(&self, user_id: &str) -> Result<Arc<[Preference]>, Error> {
+        Preference::list(self, user_id).await
+    }
+
+    async fn remove_preference(&self, user_id: &str, preference: &Preference) -> Result<u8, Error> {
+        preference.remove(self, user_id).await
+    }
+
+    async fn add_advisor_preference(
+        &self,
+        user_id: &str,
+        preference: &AdvisorPreference,
+    ) -> Result<u8, Error> {
+        preference.add(self, user_id).await
+    }
+
+    async fn get_advisor_preferences(
+        &self,
+        user_id: &str,
+    ) -> Result<Arc<[AdvisorPreference]>, Error> {
+        AdvisorPreference::list(self, user_id).await
+    }
+
+    async fn remove_advisor_preference(
+        &self,
+        user_id: &str,
+        preference: &AdvisorPreference,
+    ) -> Result<u8, Error> {
+        preference.remove(self, user_id).await
+    }
+
+    async fn set_group(&self, user_id: &str, group: &Group) -> Result<u8, Error> {
+        group.set(self, user_id).await
+    }
+
+    async fn get_groups(&self, user_id: &str) -> Result<Arc<[Group]>, Error> {
+        Group::list(self, user_id).await
+    }
+
+    async fn remove_group(&self, user_id: &str, name: &str) -> Result<u8, Error> {
+        Group::remove(self, user_id, name).await
+    }
+
+    async fn get_grade_levels(&self, user_id: &str) -> Result<GradeLevels, Error> {
+        GradeLevels::get(self, user_id).await
+    }
+
+    async fn set_grade_levels(&self, user_id: &str, levels: &GradeLevels) -> Result<u8, Error> {
+        levels.set(self, user_id).await
+    }
+
+    async fn save_organization(
+        &self,
+        user_id: &str,
+        name: Arc<str>,
+        settings: &Settings,
+        organization: &Organization,
+    ) -> Result<VersionInfo, Error> {
+        SavedOrganization::save(self, user_id, name, settings, organization).await
+    }
+
+    async fn restore_organization(
+        &self,
+        user_id: &str,
+        info: &VersionInfo,
+        settings: &Settings,
+        organization: &Organization,
+    ) -> Result<u8, Error> {
+        SavedOrganization::restore(self, user_id, info, settings, organization).await
+    }
+
+    async fn list_organizations(&self, user_id: &str) -> Result<Arc<[VersionInfo]>, Error> {
+        SavedOrganization::list(self, user_id).await
+    }
+
+    async fn get_organization(&self, user_id: &str, id: &str) -> Result<SavedOrganization, Error> {
+        SavedOrganization::get(self, user_id, id).await
+    }
+
+    async fn update_organization(
+        &self,
+        user_id: &str,
+        id: &str,
+        organization: &Organization,
+    ) -> Result<u8, Error> {
+        SavedOrganization::update(self, user_id, id, organization).await
+    }
+
+    async fn rename_organization(
+        &self,
+        user_id: &str,
+        id: &str,
+        name: Arc<str>,
+    ) -> Result<VersionInfo, Error> {
+        SavedOrganization::rename(self, user_id, id, name).await
+    }
+
+    async fn delete_organization(&self, user_id: &str, id: &str) -> Result<u8, Error> {
+        SavedOrganization::delete(self, user_id, id).await
+    }
+}
@@ -0,0 +1,119 @@
+use crate::{
+    advisories::{Organization, SavedOrganization, Settings, VersionInfo},
+    people::{AdvisorPreference, GradeLevels, Group, Person, Preference, Student, Teacher},
+    Error,
+};
+use std::sync::Arc;
+
+/// Storage for each account's people, the relationships between them, and their saved organizations
+///
+/// Handlers only use storage through this trait, so the server can run on the [`neo4rs`] database or on a
+/// [`super::MemoryRepository`]. Every method is scoped to the account with the given `user_id`, and adding
+/// something that is already stored leaves a single copy
+#[async_trait::async_trait]
+pub trait Repository: Send + Sync {
+    /// Get every student and teacher, along with everyone they are banned from
+    async fn get_people(&self, user_id: &str) -> Result<Arc<[Person]>, Error>;
+    /// Remove every student, teacher and group, along with their relationships
+    ///
+    /// Grade levels and saved organizations are kept
+    async fn clear_people(&self, user_id: &str) -> Result<u8, Error>;
+
+    /// Add teachers
+    async fn add_teachers(&self, user_id: &str, teachers: &[Teacher]) -> Result<u8, Error>;
+    /// Get every teacher
+    async fn get_teachers(&self, user_id: &str) -> Result<Arc<[Teacher]>, Error>;
+    /// Add students, linking each to the teachers in its list that are already stored
+    ///
+    /// Names are unique within an account, so a student with the same name as a stored student replaces its
+    /// grade and attributes and gains any new teachers
+    async fn add_students(&self, user_id: &str, students: &[Student]) -> Result<u8, Error>;
+    /// Get every student, ordered by name, along with their teachers, bans and preferences
+    async fn get_students(&self, user_id: &str) -> Result<Arc<[Student]>, Error>;
+
+    /// Ban two stored people from being together, as students, advisor and student, or coadvisors, failing
+    /// with [`Error::Invalid`] if either isn't stored
+    async fn ban_pair(&self, user_id: &str, pair: [Person; 2]) -> Result<u8, Error>;
+    /// Get every pair of teachers that are banned from advising the same advisory, each ordered by name
+    async fn get_coadvisor_bans(&self, user_id: &str)
+        -> Result<Arc<[(Arc<str>, Arc<str>)]>, Error>;
+
+    /// Record a student's request to be placed with a peer, if both are stored
+    async fn add_preference(&self, user_id: &str, preference: &Preference) -> Result<u8, Error>;
+    /// Get every request to be placed with a peer, ordered by the names of the students
+    async fn get_preferences(&self, user_id: &str) -> Result<Arc<[Preference]>, Error>;
+    /// Withdraw a student's request to be placed with a peer
+    async fn remove_preference(&self, user_id: &str, preference: &Preference) -> Result<u8, Error>;
+
+    /// Record a student's request to be advised by a teacher, if both are stored
+    async fn add_advisor_preference(
+        &self,
+        user_id: &str,
+        preference: &AdvisorPreference,
+    ) -> Result<u8, Error>;
+    /// Get every request to be advised by a teacher, ordered by the names of the student and teacher
+    async fn get_advisor_preferences(
+        &self,
+        user_id: &str,
+    ) -> Result<Arc<[AdvisorPreference]>, Error>;
+    /// Withdraw a student's request to be advised by a teacher
+    async fn remove_advisor_preference(
+        &self,
+        user_id: &str,
+        preference: &AdvisorPreference,
+    ) -> Result<u8, Error>;
+
+    /// Save a group with the members that are stored, replacing any group with its name
+    async fn set_group(&self, user_id: &str, group: &Group) -> Result<u8, Error>;
+    /// Get every group, ordered by name, with its members ordered by name
+    async fn get_groups(&self, user_id: &str) -> Result<Arc<[Group]>, Error>;
+    /// Remove a group by its name, keeping its members
+    async fn remove_group(&self, user_id: &str, name: &str) -> Result<u8, Error>;
+
+    /// Get the account's grade levels, or the default high school grades if none were set
+    async fn get_grade_levels(&self, user_id: &str) -> Result<GradeLevels, Error>;
+    /// Set the account's grade levels, replacing any that were set before
+    async fn set_grade_levels(&self, user_id: &str, levels: &GradeLevels) -> Result<u8, Error>;
+
+    /// Save an organization under a name, along with the settings it was generated with
+    async fn save_organization(
+        &self,
+        user_id: &str,
+        name: Arc<str>,
+        settings: &Settings,
+        organization: &Organization,
+    ) -> Result<VersionInfo, Error>;
+    /// Save an organization under the id, name and creation time it was first saved with, such as when it is
+    /// copied from other storage
+    async fn restore_organization(
+        &self,
+        user_id: &str,
+        info: &VersionInfo,
+        settings: &Settings,
+        organization: &Organization,
+    ) -> Result<u8, Error>;
+    /// List every saved organization, newest first
+    async fn list_organizations(&self, user_id: &str) -> Result<Arc<[VersionInfo]>, Error>;
+    /// Fetch a saved organization by its id, with its students filled in from the stored students
+    ///
+    /// Fails with a `404 Not Found` status if no organization has the id
+    async fn get_organization(&self, user_id: &str, id: &str) -> Result<SavedOrganization, Error>;
+    /// Replace the advisories of a saved organization, keeping its name and settings
+    async fn update_organization(
+        &self,
+        user_id: &str,
+        id: &str,
+        organization: &Organization,
+    ) -> Result<u8, Error>;
+    /// Give a saved organization a new name
+    ///
+    /// Fails with a `404 Not Found` status if no organization has the id
+    async fn rename_organization(
+        &self,
+        user_id: &str,
+        id: &str,
+        name: Arc<str>,
+    ) -> Result<VersionInfo, Error>;
+    /// Delete a saved organization
+    async fn delete_organization(&self, user_id: &str, id: &str) -> Result<u8, Error>;
+}
@@ -0,0 +1,806 @@
+use super::Repository;
+use crate::{
+    advisories::{Organization, Placement, SavedOrganization, Settings, VersionInfo},
+    people::{
+        AdvisorPreference, Attributes, Grade, GradeLevels, Group, Person, Preference, Student,
+        Teacher,
+    },
+    Error, Problem,
+};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+/// Tables of every account, each row marked by its account's `user_id`
+///
+/// People are stored by name, which is unique within an account the same as in the other repositories, and
+/// relationships by the names of the people in them. Organizations keep their settings and the names in each
+/// advisory as JSON
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS teachers (
+        user_id TEXT NOT NULL, name TEXT NOT NULL,
+        PRIMARY KEY (user_id, name)
+    );
+    CREATE TABLE IF NOT EXISTS students (
+        user_id TEXT NOT NULL, name TEXT NOT NULL, grade INTEGER NOT NULL, attributes TEXT NOT NULL,
+        PRIMARY KEY (user_id, name)
+    );
+    CREATE TABLE IF NOT EXISTS teaches (
+        user_id TEXT NOT NULL, teacher TEXT NOT NULL, student TEXT NOT NULL,
+        PRIMARY KEY (user_id, teacher, student)
+    );
+    CREATE TABLE IF NOT EXISTS bans (
+        user_id TEXT NOT NULL, first TEXT NOT NULL, second TEXT NOT NULL,
+        PRIMARY KEY (user_id, first, second)
+    );
+    CREATE TABLE IF NOT EXISTS preferences (
+        user_id TEXT NOT NULL, student TEXT NOT NULL, preferred TEXT NOT NULL,
+        PRIMARY KEY (user_id, student, preferred)
+    );
+    CREATE TABLE IF NOT EXISTS advisor_preferences (
+        user_id TEXT NOT NULL, student TEXT NOT NULL, advisor TEXT NOT NULL,
+        PRIMARY KEY (user_id, student, advisor)
+    );
+    CREATE TABLE IF NOT EXISTS groups (
+        user_id TEXT NOT NULL, name TEXT NOT NULL,
+        PRIMARY KEY (user_id, name)
+    );
+    CREATE TABLE IF NOT EXISTS group_members (
+        user_id TEXT NOT NULL, group_name TEXT NOT NULL, member TEXT NOT NULL,
+        PRIMARY KEY (user_id, group_name, member)
+    );
+    CREATE TABLE IF NOT EXISTS grade_levels (
+        user_id TEXT NOT NULL PRIMARY KEY, levels TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS organizations (
+        user_id TEXT NOT NULL, id TEXT NOT NULL, name TEXT NOT NULL, created TEXT NOT NULL,
+        settings TEXT NOT NULL, placements TEXT NOT NULL,
+        PRIMARY KEY (user_id, id)
+    );
+";
+
+/// Statements that fail are logged, and only reported to the client as an internal error
+impl From<rusqlite::Error> for Error {
+    fn from(error: rusqlite::Error) -> Self {
+        log::warn!(
+            "Unable to run a statement on the SQLite database: {}",
+            error
+        );
+        axum::http::StatusCode::INTERNAL_SERVER_ERROR.into()
+    }
+}
+
+/// Whether a teacher with the name is stored
+fn is_teacher(connection: &Connection, user_id: &str, name: &str) -> rusqlite::Result<bool> {
+    connection
+        .prepare_cached("SELECT 1 FROM teachers WHERE user_id = ?1 AND name = ?2")?
+        .exists(params![user_id, name])
+}
+
+/// Whether a student with the name is stored
+fn is_student(connection: &Connection, user_id: &str, name: &str) -> rusqlite::Result<bool> {
+    connection
+        .prepare_cached("SELECT 1 FROM students WHERE user_id = ?1 AND name = ?2")?
+        .exists(params![user_id, name])
+}
+
+/// Names from the first column of a query's rows
+fn names(
+    connection: &Connection,
+    query: &str,
+    params: impl rusqlite::Params,
+) -> rusqlite::Result<Vec<Arc<str>>> {
+    connection
+        .prepare_cached(query)?
+        .query_map(params, |row| row.get::<_, String>(0).map(Arc::from))?
+        .collect()
+}
+
+/// Pairs of names from the first two columns of a query's rows
+fn pairs(
+    connection: &Connection,
+    query: &str,
+    params: impl rusqlite::Params,
+) -> rusqlite::Result<Vec<(Arc<str>, Arc<str>)>> {
+    connection
+        .prepare_cached(query)?
+        .query_map(params, |row| {
+            Ok((
+                Arc::from(row.get::<_, String>(0)?),
+                Arc::from(row.get::<_, String>(1)?),
+            ))
+        })?
+        .collect()
+}
+
+/// Names of everyone that each person is banned from being with, by the person's name
+fn bans(
+    connection: &Connection,
+    user_id: &str,
+) -> rusqlite::Result<HashMap<Arc<str>, Vec<Arc<str>>>> {
+    let mut banned: HashMap<Arc<str>, Vec<Arc<str>>> = HashMap::new();
+    let query = "SELECT first, second FROM bans WHERE user_id = ?1 ORDER BY rowid";
+    for (first, second) in pairs(connection, query, [user_id])? {
+        banned
+            .entry(first.clone())
+            .or_default()
+            .push(second.clone());
+        banned.entry(second).or_default().push(first);
+    }
+    Ok(banned)
+}
+
+/// Parse a column holding JSON, describing it as a problem with the record if it can't be read
+fn json<T: serde::de::DeserializeOwned>(field: &str, value: &str) -> Result<T, Problem> {
+    serde_json::from_str(value).map_err(|e| Problem::new(field, format!("is not valid: {}", e)))
+}
+
+/// Names that each person asked for in a table of requests, such as the peers each student asked to be
+/// placed with, by the person's name
+fn requests(
+    connection: &Connection,
+    query: &str,
+    user_id: &str,
+) -> rusqlite::Result<HashMap<Arc<str>, Vec<Arc<str>>>> {
+    let mut requested: HashMap<Arc<str>, Vec<Arc<str>>> = HashMap::new();
+    for (person, name) in pairs(connection, query, [user_id])? {
+        requested.entry(person).or_default().push(name);
+    }
+    Ok(requested)
+}
+
+/// Every student, ordered by name, along with their teachers, bans and preferences
+///
+/// A ban with a name that belongs to both a teacher and a student is read as a ban of the teacher. Fails with every problem found if a student's grade or attributes can't be read
+fn students(connection: &Connection, user_id: &str) -> Result<Arc<[Student]>, Error> {
+    let rows: Vec<(String, i64, String)> = connection
+        .prepare_cached(
+            "SELECT name, grade, attributes FROM students WHERE user_id = ?1 ORDER BY name",
+        )?
+        .query_map([user_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+    let teachers: HashSet<Arc<str>> = names(
+        connection,
+        "SELECT name FROM teachers WHERE user_id = ?1",
+        [user_id],
+    )?
+    .into_iter()
+    .collect();
+    let mut teaches: HashMap<Arc<str>, Vec<Teacher>> = HashMap::new();
+    let query = "SELECT student, teacher FROM teaches WHERE user_id = ?1 ORDER BY rowid";
+    for (student, teacher) in pairs(connection, query, [user_id])? {
+        teaches
+            .entry(student)
+            .or_default()
+            .push(Teacher::new(teacher));
+    }
+    let banned = bans(connection, user_id)?;
+    let mut preferred = requests(
+        connection,
+        "SELECT student, preferred FROM preferences WHERE user_id = ?1 ORDER BY rowid",
+        user_id,
+    )?;
+    let mut preferred_advisors = requests(
+        connection,
+        "SELECT student, advisor FROM advisor_preferences WHERE user_id = ?1 ORDER BY rowid",
+        user_id,
+    )?;
+
+    let mut students = Vec::new();
+    let mut problems = Vec::new();
+    for (index, (name, level, attributes)) in rows.into_iter().enumerate() {
+        let grade = Grade::try_from(level).map_err(|message| Problem::new("grade", message));
+        let attributes = json::<Attributes>("attributes", &attributes);
+        let (grade, attributes) = match (grade, attributes) {
+            (Ok(grade), Ok(attributes)) => (grade, attributes),
+            (grade, attributes) => {
+                let found = [grade.err(), attributes.err()].into_iter().flatten();
+                problems.extend(found.map(|p| p.at(index)));
+                continue;
+            }
+        };
+        let name: Arc<str> = Arc::from(name);
+        let banned = banned.get(&name).map(Vec::as_slice).unwrap_or_default();
+        students.push(Student {
+            teachers: teaches.remove(&name).unwrap_or_default().into(),
+            grade,
+            attributes,
+            banned_pairings: banned
+                .iter()
+                .filter(|n| !teachers.contains(*n))
+                .cloned()
+                .collect(),
+            banned_advisors: banned
+                .iter()
+                .filter(|n| teachers.contains(*n))
+                .cloned()
+                .collect(),
+            preferred_pairings: preferred.remove(&name).unwrap_or_default().into(),
+            preferred_advisors: preferred_advisors.remove(&name).unwrap_or_default().into(),
+            name,
+        });
+    }
+    Error::check_records(problems)?;
+    Ok(students.into())
+}
+
+/// Storage in an embedded [SQLite](https://sqlite.org) database file, which needs no database server
+///
+/// Behaves the same as the other repositories, so the server can switch to it once an account's data is
+/// copied over with [`super::copy_account`]
+///
+/// # Example
+///
+/// ```
+/// # use advisory_backend_lib::{people::{Student, Teacher}, storage::{Repository, SqliteRepository}};
+/// # use std::sync::Arc;
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), advisory_backend_lib::Error> {
+/// let repository = SqliteRepository::open(":memory:")?;
+/// let teacher = Teacher::new("Teacher");
+/// repository.add_teachers("user", &[teacher.clone()]).await?;
+/// let student = Student {
+///     name: Arc::from("Student"),
+///     teachers: Arc::from([teacher.clone()]),
+///     ..Default::default()
+/// };
+/// repository.add_students("user", &[student.clone()]).await?;
+/// repository.ban_pair("user", [teacher.clone().into(), student.into()]).await?;
+///
+/// let students = repository.get_students("user").await?;
+/// assert_eq!(students[0].teachers[..], [teacher]);
+/// assert_eq!(students[0].banned_advisors[..], [Arc::from("Teacher")]);
+/// assert!(repository.get_students("someone else").await?.is_empty());
+/// # Ok(())
+/// # }
+/// ```
+pub struct SqliteRepository {
+    /// Connection to the database, which runs one statement at a time
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl SqliteRepository {
+    /// Opens the database at a path, creating the file and its tables if they don't exist yet
+    ///
+    /// The path `:memory:` opens a database that is lost when the repository is dropped. Opening the file
+    /// blocks, so async code should open it on a blocking thread
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let connection = Connection::open(path)?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    /// Run a function on the connection, on a blocking thread so statements don't hold up the async runtime
+    async fn with_connection<R: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut Connection) -> Result<R, Error> + Send + 'static,
+    ) -> Result<R, Error> {
+        let connection = self.connection.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = connection.lock().unwrap_or_else(PoisonError::into_inner);
+            f(&mut connection)
+        })
+        .await
+        .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?
+    }
+}
+
+#[async_trait::async_trait]
+impl Repository for SqliteRepository {
+    async fn get_people(&self, user_id: &str) -> Result<Arc<[Person]>, Error> {
+        let user_id = user_id.to_owned();
+        self.with_connection(move |connection| {
+            let banned = bans(connection, &user_id)?;
+            let mut people: Vec<Person> = names(
+                connection,
+                "SELECT name FROM teachers WHERE user_id = ?1 \
+                    UNION ALL SELECT name FROM students WHERE user_id = ?1",
+                [&user_id],
+            )?
+            .into_iter()
+            .map(|name| Person {
+                banned_pairings: banned.get(&name).cloned().unwrap_or_default().into(),
+                name,
+            })
+            .collect();
+            people.sort_by(|a, b| a.name.cmp(&b.name));
+            Ok(people.into())
+        })
+        .await
+    }
+
+    async fn clear_people(&self, user_id: &str) -> Result<u8, Error> {
+        let user_id = user_id.to_owned();
+        self.with_connection(move |connection| {
+            let transaction = connection.transaction()?;
+            for table in [
+                "teachers",
+                "students",
+                "teaches",
+                "bans",
+                "preferences",
+                "advisor_preferences",
+                "groups",
+                "group_members",
+            ] {
+                transaction.execute(
+                    &format!("DELETE FROM {} WHERE user_id = ?1", table),
+                    [&user_id],
+                )?;
+            }
+            transaction.commit()?;
+            Ok(1)
+        })
+        .await
+    }
+
+    async fn add_teachers(&self, user_id: &str, teachers: &[Teacher]) -> Result<u8, Error> {
+        let user_id = user_id.to_owned();
+        let teachers = teachers.to_vec();
+        self.with_connection(move |connection| {
+            let transaction = connection.transaction()?;
+            for teacher in teachers {
+                transaction.execute(
+                    "INSERT OR IGNORE INTO teachers (user_id, name) VALUES (?1, ?2)",
+                    params![user_id, &*teacher.name],
+                )?;
+            }
+            transaction.commit()?;
+            Ok(1)
+        })
+        .await
+    }
+
+    async fn get_teachers(&self, user_id: &str) -> Result<Arc<[Teacher]>, Error> {
+        let user_id = user_id.to_owned();
+        self.with_connection(move |connection| {
+            let query = "SELECT name FROM teachers WHERE user_id = ?1 ORDER BY rowid";
+            Ok(names(connection, query, [&user_id])?
+                .into_iter()
+                .map(Teacher::new)
+                .collect())
+        })
+        .await
+    }
+
+    async fn add_students(&self, user_id: &str, students: &[Student]) -> Result<u8, Error> {
+        let user_id = user_id.to_owned();
+        let students = students
+            .iter()
+            .map(|s| Ok((s.clone(), serde_json::to_string(&s.attributes)?)))
+            .collect::<Result<Vec<_>, serde_json::Error>>()
+            .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
+        self.with_connection(move |connection| {
+            let transaction = connection.transaction()?;
+            for (student, attributes) in students {
+                transaction.execute(
+                    "INSERT INTO students (user_id, name, grade, attributes) VALUES (?1, ?2, ?3, ?4) \
+                        ON CONFLICT (user_id, name) DO UPDATE SET grade = excluded.grade, attributes = excluded.attributes",
+                    params![user_id, &*student.name, i64::from(student.grade), attributes],
+                )?;
+                for teacher in student.teachers.iter() {
+                    transaction.execute(
+                        "INSERT OR IGNORE INTO teaches (user_id, teacher, student) \
+                            SELECT user_id, name, ?3 FROM teachers WHERE user_id = ?1 AND name = ?2",
+                        params![user_id, &*teacher.name, &*student.name],
+                    )?;
+                }
+            }
+            transaction.commit()?;
+            Ok(1)
+        })
+        .await
+    }
+
+    async fn get_students(&self, user_id: &str) -> Result<Arc<[Student]>, Error> {
+        let user_id = user_id.to_owned();
+        self.with_connection(move |connection| students(connection, &user_id))
+            .await
+    }
+
+    async fn ban_pair(&self, user_id: &str, pair: [Person; 2]) -> Result<u8, Error> {
+        let user_id = user_id.to_owned();
+        self.with_connection(move |connection| {
+            let stored = |name: &str| -> rusqlite::Result<bool> {
+                Ok(is_student(connection, &user_id, name)?
+                    || is_teacher(connection, &user_id, name)?)
+            };
+            Person::check_stored(&pair, [stored(&pair[0].name)?, stored(&pair[1].name)?])?;
+            let [first, second] = pair;
+            if first.name != second.name {
+                let (first, second) = match first.name < second.name {
+                    true => (first.name, second.name),
+                    false => (second.name, first.name),
+                };
+                connection.execute(
+                    "INSERT OR IGNORE INTO bans (user_id, first, second) VALUES (?1, ?2, ?3)",
+                    params![user_id, &*first, &*second],
+                )?;
+            }
+            Ok(1)
+        })
+        .await
+    }
+
+    async fn get_coadvisor_bans(
+        &self,
+        user_id: &str,
+    ) -> Result<Arc<[(Arc<str>, Arc<str>)]>, Error> {
+        let user_id = user_id.to_owned();
+        self.with_connection(move |connection| {
+            Ok(pairs(
+                connection,
+                "SELECT first, second FROM bans WHERE user_id = ?1 \
+                    AND first IN (SELECT name FROM teachers WHERE user_id = ?1) \
+                    AND second IN (SELECT name FROM teachers WHERE user_id = ?1) \
+                    ORDER BY first, second",
+                [&user_id],
+            )?
+            .into())
+        })
+        .await
+    }
+
+    async fn add_preference(&self, user_id: &str, preference: &Preference) -> Result<u8, Error> {
+        let user_id = user_id.to_owned();
+        let preference = preference.clone();
+        self.with_connection(move |connection| {
+            if is_student(connection, &user_id, &preference.student)?
+                && is_student(connection, &user_id, &preference.preferred)?
+            {
+                connection.execute(
+                    "INSERT OR IGNORE INTO preferences (user_id, student, preferred) VALUES (?1, ?2, ?3)",
+                    params![user_id, &*preference.student, &*preference.preferred],
+                )?;
+            }
+            Ok(1)
+        })
+        .await
+    }
+
+    async fn get_preferences(&self, user_id: &str) -> Result<Arc<[Preference]>, Error> {
+        let user_id = user_id.to_owned();
+        self.with_connection(move |connection| {
+            Ok(pairs(
+                connection,
+                "SELECT student, preferred FROM preferences WHERE user_id = ?1 ORDER BY student, preferred",
+                [&user_id],
+            )?
+            .into_iter()
+            .map(|(student, preferred)| Preference { student, preferred })
+            .collect())
+        })
+        .await
+    }
+
+    async fn remove_preference(&self, user_id: &str, preference: &Preference) -> Result<u8, Error> {
+        let user_id = user_id.to_owned();
+        let preference = preference.clone();
+        self.with_connection(move |connection| {
+            connection.execute(
+                "DELETE FROM preferences WHERE user_id = ?1 AND student = ?2 AND preferred = ?3",
+                params![user_id, &*preference.student, &*preference.preferred],
+            )?;
+            Ok(1)
+        })
+        .await
+    }
+
+    async fn add_advisor_preference(
+        &self,
+        user_id: &str,
+        preference: &AdvisorPreference,
+    ) -> Result<u8, Error> {
+        let user_id = user_id.to_owned();
+        let preference = preference.clone();
+        self.with_connection(move |connection| {
+            if is_student(connection, &user_id, &preference.student)?
+                && is_teacher(connection, &user_id, &preference.advisor)?
+            {
+                connection.execute(
+                    "INSERT OR IGNORE INTO advisor_preferences (user_id, student, advisor) VALUES (?1, ?2, ?3)",
+                    params![user_id, &*preference.student, &*preference.advisor],
+                )?;
+            }
+            Ok(1)
+        })
+        .await
+    }
+
+    async fn get_advisor_preferences(
+        &self,
+        user_id: &str,
+    ) -> Result<Arc<[AdvisorPreference]>, Error> {
+        let user_id = user_id.to_owned();
+        self.with_connection(move |connection| {
+            Ok(pairs(
+                connection,
+                "SELECT student, advisor FROM advisor_preferences WHERE user_id = ?1 ORDER BY student, advisor",
+                [&user_id],
+            )?
+            .into_iter()
+            .map(|(student, advisor)| AdvisorPreference { student, advisor })
+            .collect())
+        })
+        .await
+    }
+
+    async fn remove_advisor_preference(
+        &self,
+        user_id: &str,
+        preference: &AdvisorPreference,
+    ) -> Result<u8, Error> {
+        let user_id = user_id.to_owned();
+        let preference = preference.clone();
+        self.with_connection(move |connection| {
+            connection.execute(
+                "DELETE FROM advisor_preferences WHERE user_id = ?1 AND student = ?2 AND advisor = ?3",
+                params![user_id, &*preference.student, &*preference.advisor],
+            )?;
+            Ok(1)
+        })
+        .await
+    }
+
+    async fn set_group(&self, user_id: &str, group: &Group) -> Result<u8, Error> {
+        let user_id = user_id.to_owned();
+        let group = group.clone();
+        self.with_connection(move |connection| {
+            let transaction = connection.transaction()?;
+            transaction.execute(
+                "DELETE FROM group_members WHERE user_id = ?1 AND group_name = ?2",
+                params![user_id, &*group.name],
+            )?;
+            transaction.execute(
+                "INSERT OR IGNORE INTO groups (user_id, name) VALUES (?1, ?2)",
+                params![user_id, &*group.name],
+            )?;
+            for member in group.members.iter() {
+                if is_student(&transaction, &user_id, member)? {
+                    transaction.execute(
+                        "INSERT OR IGNORE INTO group_members (user_id, group_name, member) VALUES (?1, ?2, ?3)",
+                        params![user_id, &*group.name, &**member],
+                    )?;
+                }
+            }
+            transaction.commit()?;
+            Ok(1)
+        })
+        .await
+    }
+
+    async fn get_groups(&self, user_id: &str) -> Result<Arc<[Group]>, Error> {
+        let user_id = user_id.to_owned();
+        self.with_connection(move |connection| {
+            let mut members: HashMap<Arc<str>, Vec<Arc<str>>> = HashMap::new();
+            let query =
+                "SELECT group_name, member FROM group_members WHERE user_id = ?1 ORDER BY member";
+            for (group, member) in pairs(connection, query, [&user_id])? {
+                members.entry(group).or_default().push(member);
+            }
+            let query = "SELECT name FROM groups WHERE user_id = ?1 ORDER BY name";
+            Ok(names(connection, query, [&user_id])?
+                .into_iter()
+                .map(|name| Group {
+                    members: members.remove(&name).unwrap_or_default().into(),
+                    name,
+                })
+                .collect())
+        })
+        .await
+    }
+
+    async fn remove_group(&self, user_id: &str, name: &str) -> Result<u8, Error> {
+        let user_id = user_id.to_owned();
+        let name = name.to_owned();
+        self.with_connection(move |connection| {
+            let transaction = connection.transaction()?;
+            transaction.execute(
+                "DELETE FROM group_members WHERE user_id = ?1 AND group_name = ?2",
+                params![user_id, name],
+            )?;
+            transaction.execute(
+                "DELETE FROM groups WHERE user_id = ?1 AND name = ?2",
+                params![user_id, name],
+            )?;
+            transaction.commit()?;
+            Ok(1)
+        })
+        .await
+    }
+
+    async fn get_grade_levels(&self, user_id: &str) -> Result<GradeLevels, Error> {
+        let user_id = user_id.to_owned();
+        self.with_connection(move |connection| {
+            let levels: Option<String> = connection
+                .query_row(
+                    "SELECT levels FROM grade_levels WHERE user_id = ?1",
+                    [&user_id],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            match levels {
+                Some(levels) => json::<Vec<i64>>("levels", &levels)
+                    .and_then(|levels| {
+                        levels
+                            .into_iter()
+                            .map(Grade::try_from)
+                            .collect::<Result<Arc<[Grade]>, _>>()
+                            .map_err(|message| Problem::new("levels", message))
+                    })
+                    .map(|levels| GradeLevels { levels })
+                    .map_err(|problem| Error::Corrupt(Arc::new([problem]))),
+                None => Ok(GradeLevels::default()),
+            }
+        })
+        .await
+    }
+
+    async fn set_grade_levels(&self, user_id: &str, levels: &GradeLevels) -> Result<u8, Error> {
+        let user_id = user_id.to_owned();
+        let levels = serde_json::to_string(levels)
+            .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
+        self.with_connection(move |connection| {
+            connection.execute(
+                "INSERT OR REPLACE INTO grade_levels (user_id, levels) VALUES (?1, ?2)",
+                params![user_id, levels],
+            )?;
+            Ok(1)
+        })
+        .await
+    }
+
+    async fn save_organization(
+        &self,
+        user_id: &str,
+        name: Arc<str>,
+        settings: &Settings,
+        organization: &Organization,
+    ) -> Result<VersionInfo, Error> {
+        let info = VersionInfo::new(name);
+        self.restore_organization(user_id, &info, settings, organization)
+            .await?;
+        Ok(info)
+    }
+
+    async fn restore_organization(
+        &self,
+        user_id: &str,
+        info: &VersionInfo,
+        settings: &Settings,
+        organization: &Organization,
+    ) -> Result<u8, Error> {
+        let user_id = user_id.to_owned();
+        let info = info.clone();
+        let (settings, placements) = serde_json::to_string(settings)
+            .and_then(|s| Ok((s, serde_json::to_string(&organization.placements())?)))
+            .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
+        self.with_connection(move |connection| {
+            connection.execute(
+                "INSERT OR REPLACE INTO organizations (user_id, id, name, created, settings, placements) \
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![user_id, &*info.id, &*info.name, &*info.created, settings, placements],
+            )?;
+            Ok(1)
+        })
+        .await
+    }
+
+    async fn list_organizations(&self, user_id: &str) -> Result<Arc<[VersionInfo]>, Error> {
+        let user_id = user_id.to_owned();
+        self.with_connection(move |connection| {
+            Ok(connection
+                .prepare_cached(
+                    "SELECT id, name, created FROM organizations WHERE user_id = ?1 ORDER BY created DESC",
+                )?
+                .query_map([&user_id], |row| {
+                    Ok(VersionInfo {
+                        id: Arc::from(row.get::<_, String>(0)?),
+                        name: Arc::from(row.get::<_, String>(1)?),
+                        created: Arc::from(row.get::<_, String>(2)?),
+                    })
+                })?
+                .collect::<rusqlite::Result<_>>()?)
+        })
+        .await
+    }
+
+    async fn get_organization(&self, user_id: &str, id: &str) -> Result<SavedOrganization, Error> {
+        let user_id = user_id.to_owned();
+        let id = id.to_owned();
+        self.with_connection(move |connection| {
+            let (name, created, settings, placements): (String, String, String, String) = connection
+                .query_row(
+                    "SELECT name, created, settings, placements FROM organizations WHERE user_id = ?1 AND id = ?2",
+                    params![user_id, id],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+                )
+                .optional()?
+                .ok_or(axum::http::StatusCode::NOT_FOUND)?;
+            let settings = json::<Settings>("settings", &settings);
+            let placements = json::<Vec<Placement>>("placements", &placements);
+            let (settings, placements) = match (settings, placements) {
+                (Ok(settings), Ok(placements)) => (settings, placements),
+                (settings, placements) => {
+                    let problems = [settings.err(), placements.err()].into_iter().flatten();
+                    return Err(Error::Corrupt(problems.collect()));
+                }
+            };
+            let students = students(connection, &user_id)?;
+            Ok(SavedOrganization {
+                info: VersionInfo {
+                    id: Arc::from(id),
+                    name: Arc::from(name),
+                    created: Arc::from(created),
+                },
+                organization: Organization::rebuild(&settings, &placements, &students),
+                settings,
+            })
+        })
+        .await
+    }
+
+    async fn update_organization(
+        &self,
+        user_id: &str,
+        id: &str,
+        organization: &Organization,
+    ) -> Result<u8, Error> {
+        let user_id = user_id.to_owned();
+        let id = id.to_owned();
+        let placements = serde_json::to_string(&organization.placements())
+            .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
+        self.with_connection(move |connection| {
+            connection.execute(
+                "UPDATE organizations SET placements = ?3 WHERE user_id = ?1 AND id = ?2",
+                params![user_id, id, placements],
+            )?;
+            Ok(1)
+        })
+        .await
+    }
+
+    async fn rename_organization(
+        &self,
+        user_id: &str,
+        id: &str,
+        name: Arc<str>,
+    ) -> Result<VersionInfo, Error> {
+        let user_id = user_id.to_owned();
+        let id = id.to_owned();
+        self.with_connection(move |connection| {
+            let created: String = connection
+                .query_row(
+                    "UPDATE organizations SET name = ?3 WHERE user_id = ?1 AND id = ?2 RETURNING created",
+                    params![user_id, id, &*name],
+                    |row| row.get(0),
+                )
+                .optional()?
+                .ok_or(axum::http::StatusCode::NOT_FOUND)?;
+            Ok(VersionInfo {
+                id: Arc::from(id),
+                name,
+                created: Arc::from(created),
+            })
+        })
+        .await
+    }
+
+    async fn delete_organization(&self, user_id: &str, id: &str) -> Result<u8, Error> {
+        let user_id = user_id.to_owned();
+        let id = id.to_owned();
+        self.with_connection(move |connection| {
+            connection.execute(
+                "DELETE FROM organizations WHERE user_id = ?1 AND id = ?2",
+                params![user_id, id],
+            )?;
+            Ok(1)
+        })
+        .await
+    }
+}
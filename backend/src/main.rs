@@ -7,6 +7,7 @@
 //! **Notes**
 //!
 //! A custom fork of neo4rs is used to add functionality for handling vectors as a return type from neo4j
+use advisory_backend_lib::storage::{MemoryRepository, Repository, SqliteRepository};
 use anyhow::{Context, Result};
 use axum::{routing::*, Router};
 use axum_server::tls_rustls::RustlsConfig;
@@ -18,11 +19,14 @@ mod handlers {
     mod advisories;
     /// Handlers for server info and health check
     mod info;
+    /// Handlers for saving, listing and managing generated organizations
+    mod organizations;
     /// Handlers that handle adding and managing students and advisors
     mod people;
 
     pub(crate) use advisories::*;
     pub(crate) use info::*;
+    pub(crate) use organizations::*;
     pub(crate) use people::*;
 }
 
@@ -33,8 +37,8 @@ mod auth;
 #[allow(dead_code)]
 #[derive(Clone)]
 struct SharedState {
-    /// Graph for database access
-    graph: Option<Arc<neo4rs::Graph>>,
+    /// Storage for people and saved organizations, which is the database unless configured otherwise
+    repository: Arc<dyn Repository>,
     /// Keyset for JWT decoding (auth)
     keyset: Arc<jsonwebtokens_cognito::KeySet>,
     /// Verifier for JWT decoding (auth)
@@ -48,21 +52,8 @@ async fn main() -> Result<()> {
     // Setup logger
     setup_logger()?;
 
-    // Connect to database
-    let user = "neo4j";
-    let uri = match std::env::var("DB_ADDRESS") {
-        Ok(val) => format!("{}:7687", val),
-        Err(_) => "localhost:7687".to_owned(),
-    };
-    let pass = match std::env::var("DB_PASS") {
-        Ok(val) => val,
-        Err(_) => "test".to_owned(),
-    };
-    let graph = Arc::new(
-        neo4rs::Graph::new(&uri, user, &pass)
-            .await
-            .context("Unable to connect to database")?,
-    );
+    // Connect to storage
+    let repository = connect_repository().await?;
 
     // JSON webtoken setup
     let keyset =
@@ -76,7 +67,7 @@ async fn main() -> Result<()> {
 
     // State to be accessed by handlers
     let state = SharedState {
-        graph: Some(graph),
+        repository,
         keyset,
         verifier,
     };
@@ -124,6 +115,45 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Connect to the storage selected by the STORAGE environment variable
+///
+/// `memory` keeps everything in memory, so the server can run without a database, `sqlite` stores everything
+/// in the SQLite database file at SQLITE_PATH, and anything else connects to the neo4j database
+async fn connect_repository() -> Result<Arc<dyn Repository>> {
+    match std::env::var("STORAGE").as_deref() {
+        Ok("memory") => {
+            log::info!("Storing data in memory");
+            Ok(Arc::new(MemoryRepository::new()))
+        }
+        Ok("sqlite") => {
+            let path = match std::env::var("SQLITE_PATH") {
+                Ok(val) => val,
+                Err(_) => "advisory.db".to_owned(),
+            };
+            log::info!("Storing data in {}", path);
+            let repository = tokio::task::spawn_blocking(move || SqliteRepository::open(path))
+                .await?
+                .context("Unable to open SQLite database")?;
+            Ok(Arc::new(repository))
+        }
+        _ => {
+            let user = "neo4j";
+            let uri = match std::env::var("DB_ADDRESS") {
+                Ok(val) => format!("{}:7687", val),
+                Err(_) => "localhost:7687".to_owned(),
+            };
+            let pass = match std::env::var("DB_PASS") {
+                Ok(val) => val,
+                Err(_) => "test".to_owned(),
+            };
+            let graph = neo4rs::Graph::new(&uri, user, &pass)
+                .await
+                .context("Unable to connect to database")?;
+            Ok(Arc::new(graph))
+        }
+    }
+}
+
 /// Configure routes for axum server
 fn app(state: SharedState) -> Router {
     // Axum setup and configuration
@@ -136,13 +166,50 @@ fn app(state: SharedState) -> Router {
             delete(handlers::clear_people_handler).get(handlers::get_people_handler),
         )
         .route("/people/ban", post(handlers::ban_pair_handler))
+        .route("/people/prefer", post(handlers::add_preference_handler))
+        .route("/people/prefer", get(handlers::get_preferences_handler))
+        .route(
+            "/people/prefer",
+            delete(handlers::remove_preference_handler),
+        )
+        .route(
+            "/people/prefer/advisor",
+            post(handlers::add_advisor_preference_handler),
+        )
+        .route(
+            "/people/prefer/advisor",
+            get(handlers::get_advisor_preferences_handler),
+        )
+        .route(
+            "/people/prefer/advisor",
+            delete(handlers::remove_advisor_preference_handler),
+        )
         .route("/people/teacher", post(handlers::add_teacher_handler))
         .route("/people/teacher", get(handlers::get_teachers_handler))
         .route("/people/student", post(handlers::add_student_handler))
         .route("/people/student", get(handlers::get_students_handler))
+        .route("/people/groups", post(handlers::set_group_handler))
+        .route("/people/groups", get(handlers::get_groups_handler))
+        .route(
+            "/people/groups/:name",
+            delete(handlers::remove_group_handler),
+        )
+        .route("/people/grades", get(handlers::get_grade_levels_handler))
+        .route("/people/grades", put(handlers::set_grade_levels_handler))
         .route("/people/teacher/bulk", post(handlers::add_teacher_bulk))
         .route("/people/student/bulk", post(handlers::add_student_bulk))
-        .route("/", put(handlers::get_advisories));
+        .route("/", put(handlers::get_advisories))
+        .route("/regenerate", put(handlers::regenerate_advisories))
+        .route("/organizations", get(handlers::list_organizations))
+        .route("/organizations", post(handlers::save_organization))
+        .route("/organizations/:id", get(handlers::get_organization))
+        .route("/organizations/:id", patch(handlers::rename_organization))
+        .route("/organizations/:id", delete(handlers::delete_organization))
+        .route(
+            "/organizations/:id/edits",
+            post(handlers::edit_organization),
+        )
+        .route("/diff", put(handlers::diff_organizations));
     Router::new()
         // add /api before all routes
         .nest("/api", api_router)
@@ -180,3 +247,57 @@ fn setup_logger() -> Result<()> {
         .context("Failed to dispatch logger")?;
     Ok(())
 }
+
+/// Smoke tests of the router, run over a [`MemoryRepository`] so they don't need a database
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use tower::ServiceExt;
+
+    /// Router over empty storage, whose keyset is never fetched since no request sends a token
+    fn test_app() -> Router {
+        let keyset = Arc::new(
+            jsonwebtokens_cognito::KeySet::new("us-east-1", "us-east-1_Ye96rGbqV").unwrap(),
+        );
+        let verifier = Arc::new(
+            keyset
+                .new_access_token_verifier(&["5c6eva8nctpb3aug8l0teak36v"])
+                .build()
+                .unwrap(),
+        );
+        app(SharedState {
+            repository: Arc::new(MemoryRepository::new()),
+            keyset,
+            verifier,
+        })
+    }
+
+    /// Send a request without a token and return the status code of the response
+    async fn status(method: &str, uri: &str) -> StatusCode {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::empty())
+            .unwrap();
+        test_app().oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn health_check_is_served() {
+        assert_eq!(status("GET", "/api/health").await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn people_need_a_token() {
+        assert_eq!(status("GET", "/api/people").await, StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            status("GET", "/api/people/student").await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(status("GET", "/api/missing").await, StatusCode::NOT_FOUND);
+    }
+}
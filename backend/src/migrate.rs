@@ -0,0 +1,46 @@
+#![warn(missing_docs, clippy::missing_docs_in_private_items)]
+//! Tool copying accounts from the neo4j database into a SQLite database file, so the server can be switched
+//! over with `STORAGE=sqlite`
+//!
+//! Run as `migrate_to_sqlite <sqlite path> <user id>...`, with the database at DB_ADDRESS and its password in
+//! DB_PASS, the same as the server. Accounts are copied one at a time, and copying an account again leaves a
+//! single copy of its data
+use advisory_backend_lib::storage::{copy_account, SqliteRepository};
+use anyhow::{bail, Context, Result};
+
+/// Copy each account given on the command line
+#[tokio::main]
+async fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+    let (Some(path), user_ids) = (args.next(), args.collect::<Vec<String>>()) else {
+        bail!("Usage: migrate_to_sqlite <sqlite path> <user id>...");
+    };
+    if user_ids.is_empty() {
+        bail!("No accounts to copy were given");
+    }
+
+    let user = "neo4j";
+    let uri = match std::env::var("DB_ADDRESS") {
+        Ok(val) => format!("{}:7687", val),
+        Err(_) => "localhost:7687".to_owned(),
+    };
+    let pass = match std::env::var("DB_PASS") {
+        Ok(val) => val,
+        Err(_) => "test".to_owned(),
+    };
+    let graph = neo4rs::Graph::new(&uri, user, &pass)
+        .await
+        .context("Unable to connect to database")?;
+    // opening the file blocks, so it is opened on a blocking thread like the repository's statements
+    let sqlite = tokio::task::spawn_blocking(move || SqliteRepository::open(path))
+        .await?
+        .context("Unable to open SQLite database")?;
+
+    for user_id in user_ids {
+        copy_account(&graph, &sqlite, &user_id)
+            .await
+            .with_context(|| format!("Unable to copy account {}", user_id))?;
+        println!("Copied account {}", user_id);
+    }
+    Ok(())
+}
@@ -0,0 +1,29 @@
+//! Fixtures shared by the integration tests, each of which only uses some of them
+#![allow(dead_code)]
+
+use advisory_backend_lib::{
+    people::{Student, Teacher},
+    storage::{MemoryRepository, Repository, SqliteRepository},
+    Error,
+};
+use std::sync::Arc;
+
+/// Every repository that runs without a database server
+pub fn repositories() -> Vec<Box<dyn Repository>> {
+    let sqlite = SqliteRepository::open(":memory:").expect("unable to open a SQLite database");
+    vec![Box::new(MemoryRepository::new()), Box::new(sqlite)]
+}
+
+/// Add two teachers and four students, each taught by one of the teachers
+pub async fn add_people(repository: &dyn Repository, user_id: &str) -> Result<u8, Error> {
+    let teachers = [Teacher::new("Teacher 0"), Teacher::new("Teacher 1")];
+    repository.add_teachers(user_id, &teachers).await?;
+    let students: Vec<Student> = (0..4)
+        .map(|i| Student {
+            name: Arc::from(format!("Student {}", i)),
+            teachers: Arc::from([teachers[i % 2].clone()]),
+            ..Default::default()
+        })
+        .collect();
+    repository.add_students(user_id, &students).await
+}
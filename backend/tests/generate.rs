@@ -0,0 +1,91 @@
+//! Tests of generating and regenerating organizations
+
+use advisory_backend_lib::{
+    advisories::{Algorithm, Organization, Settings, TeacherGrouping},
+    people::{Grade, Student, Teacher, GENDER},
+    Error,
+};
+use std::sync::Arc;
+
+/// Students spread evenly across grades and advisors, with a third of them male
+fn school(student_count: usize, advisory_count: usize) -> (Arc<[Student]>, Settings) {
+    let grades = [
+        Grade::FRESHMAN,
+        Grade::SOPHOMORE,
+        Grade::JUNIOR,
+        Grade::SENIOR,
+    ];
+    let students = (0..student_count)
+        .map(|i| Student {
+            name: Arc::from(format!("Student {}", i)),
+            grade: grades[i % grades.len()],
+            teachers: Arc::from([Teacher::new(format!("Teacher {}", i % advisory_count))]),
+            attributes: [(
+                Arc::from(GENDER),
+                Arc::from(if i % 3 == 0 { "Male" } else { "Female" }),
+            )]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        })
+        .collect();
+    let settings = Settings {
+        num_advisories: advisory_count as u16,
+        teacher_groupings: (0..advisory_count)
+            .map(|i| TeacherGrouping::new([Teacher::new(format!("Teacher {}", i))]))
+            .collect(),
+        seed: Some(1),
+        algorithm: Algorithm::Exact,
+        ..Default::default()
+    };
+    (students, settings)
+}
+
+#[tokio::test]
+async fn exact_solves_a_few_hundred_students() -> Result<(), Error> {
+    let (students, mut settings) = school(300, 15);
+    // the longest timeout allowed, leaving room for an unoptimized build
+    settings.exact_timeout = 60_000;
+    let best = Organization::generate_best(&settings, students).await?;
+    assert_eq!(best.bound, Some(best.score.total.total));
+    Ok(())
+}
+
+#[tokio::test]
+async fn exact_rejects_too_many_students() {
+    let (students, settings) = school(1000, 15);
+    let result = Organization::generate_best(&settings, students).await;
+    assert!(matches!(result, Err(Error::Invalid(_))));
+}
+
+#[tokio::test]
+async fn regenerating_matches_each_previous_advisory_once() -> Result<(), Error> {
+    let students: Arc<[Student]> = (0..20)
+        .map(|i| Student {
+            name: Arc::from(format!("Student {}", i)),
+            ..Default::default()
+        })
+        .collect();
+    // Teacher 0 advised both previous advisories, so both share an advisor with its grouping now
+    let before = Settings {
+        num_advisories: 2,
+        teacher_groupings: Arc::from([
+            TeacherGrouping::new([Teacher::new("Teacher 0")]),
+            TeacherGrouping::new([Teacher::new("Teacher 0"), Teacher::new("Teacher 1")]),
+        ]),
+        seed: Some(42),
+        ..Default::default()
+    };
+    let previous = Organization::generate(&before, students.clone()).await?;
+    let after = Settings {
+        teacher_groupings: Arc::from([
+            TeacherGrouping::new([Teacher::new("Teacher 0")]),
+            TeacherGrouping::new([Teacher::new("Teacher 1")]),
+        ]),
+        ..before
+    };
+
+    let regenerated = Organization::regenerate(&after, &previous, students).await?;
+    assert!(regenerated.moved.is_empty());
+    Ok(())
+}
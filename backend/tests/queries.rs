@@ -0,0 +1,102 @@
+//! Regression test for names that used to be written into Cypher queries directly
+//!
+//! The queries are run against the database at `DB_ADDRESS`, with the password in `DB_PASS`, the same as the
+//! server. The test is ignored unless it is run with `cargo test -- --ignored`, since it needs a database.
+//! The query text and parameters are checked without a database by the unit tests in `people::student`
+
+use advisory_backend_lib::{
+    people::{Grade, Person, Student, Teacher},
+    DatabaseNode,
+};
+use std::sync::Arc;
+
+/// Names with quotes, backslashes and Cypher keywords, including some that would have closed the list they
+/// were written into and run a query of their own
+const NAMES: [&str; 8] = [
+    "Conan O'Brien",
+    "Dwayne \"The Rock\" Johnson",
+    "Back\\slash",
+    "Trailing\\",
+    "MATCH (n) DETACH DELETE n",
+    "\"] MATCH (n) DETACH DELETE n WITH [\"",
+    "'}) RETURN 1 //",
+    "{ name: $user_id }",
+];
+
+/// Connect to the database at `DB_ADDRESS`
+async fn connect() -> neo4rs::Graph {
+    let address =
+        std::env::var("DB_ADDRESS").expect("DB_ADDRESS must be set to run the Cypher tests");
+    let pass = std::env::var("DB_PASS").unwrap_or_else(|_| "test".to_owned());
+    neo4rs::Graph::new(&format!("{}:7687", address), "neo4j", &pass)
+        .await
+        .expect("unable to connect to the database")
+}
+
+/// Id of an account that only the calling test uses, so tests don't see each other's people
+fn test_user(test: &str) -> String {
+    format!("queries-test-{}-{}", test, std::process::id())
+}
+
+/// Names of a list of teachers, sorted so they can be compared
+fn sorted_names(teachers: &[Teacher]) -> Vec<Arc<str>> {
+    let mut names: Vec<Arc<str>> = teachers.iter().map(|t| t.name.clone()).collect();
+    names.sort();
+    names
+}
+
+#[tokio::test]
+#[ignore = "needs a neo4j database at DB_ADDRESS"]
+async fn people_with_special_names_round_trip_through_cypher() {
+    let graph = connect().await;
+    let user_id = test_user("cypher");
+    Person::clear_nodes(&graph, &user_id).await.unwrap();
+
+    let teachers: Arc<[Teacher]> = NAMES.iter().map(|&n| Teacher::new(n)).collect();
+    Teacher::add_multiple_nodes(&teachers, &graph, &user_id, true)
+        .await
+        .unwrap();
+    Teacher::new("Single \"quoted\" \\ teacher")
+        .add_node(&graph, &user_id, true)
+        .await
+        .unwrap();
+
+    let stored_teachers = Teacher::get_nodes(&graph, &user_id).await.unwrap();
+    let mut expected = sorted_names(&teachers);
+    expected.push(Arc::from("Single \"quoted\" \\ teacher"));
+    expected.sort();
+    assert_eq!(sorted_names(&stored_teachers), expected);
+
+    let students: Vec<Student> = NAMES
+        .iter()
+        .map(|&n| Student {
+            name: Arc::from(format!("Student {}", n)),
+            teachers: teachers.clone(),
+            grade: Grade::JUNIOR,
+            ..Default::default()
+        })
+        .collect();
+    Student::add_multiple_nodes(&students, &graph, &user_id, true)
+        .await
+        .unwrap();
+    let single = Student {
+        name: Arc::from("Single \"quoted\" \\ student"),
+        teachers: teachers.clone(),
+        grade: Grade::SENIOR,
+        ..Default::default()
+    };
+    single.add_node(&graph, &user_id, true).await.unwrap();
+
+    let stored = Student::get_nodes(&graph, &user_id).await.unwrap();
+    assert_eq!(stored.len(), students.len() + 1);
+    for student in students.iter().chain([&single]) {
+        let found = stored
+            .iter()
+            .find(|s| s.name == student.name)
+            .unwrap_or_else(|| panic!("{} wasn't stored", student.name));
+        assert_eq!(found.grade, student.grade);
+        assert_eq!(sorted_names(&found.teachers), sorted_names(&teachers));
+    }
+
+    Person::clear_nodes(&graph, &user_id).await.unwrap();
+}
@@ -0,0 +1,275 @@
+//! Tests of the storage that the server runs on without a database server
+
+mod common;
+
+use advisory_backend_lib::{
+    advisories::{Organization, Settings, TeacherGrouping},
+    people::{AdvisorPreference, Grade, GradeLevels, Group, Person, Preference, Student, Teacher},
+    storage::{copy_account, MemoryRepository, Repository, SqliteRepository},
+    Error,
+};
+use common::{add_people, repositories};
+use std::sync::Arc;
+
+#[tokio::test]
+async fn relationships_are_only_stored_between_stored_people() -> Result<(), Error> {
+    for repository in repositories() {
+        relationships_are_only_stored_in(&*repository).await?;
+    }
+    Ok(())
+}
+
+/// Ban, prefer and group people along with someone that isn't stored, then clear everyone
+async fn relationships_are_only_stored_in(repository: &dyn Repository) -> Result<(), Error> {
+    add_people(repository, "user").await?;
+
+    let student = |name: &str| {
+        Person::from(Student {
+            name: Arc::from(name),
+            ..Default::default()
+        })
+    };
+    repository
+        .ban_pair("user", [student("Student 0"), student("Student 1")])
+        .await?;
+    let unknown = repository
+        .ban_pair("user", [student("Student 0"), student("Nobody")])
+        .await;
+    assert!(matches!(unknown, Err(Error::Invalid(_))));
+    let preference = Preference {
+        student: Arc::from("Student 2"),
+        preferred: Arc::from("Student 3"),
+    };
+    repository.add_preference("user", &preference).await?;
+    let group = Group {
+        name: Arc::from("Siblings"),
+        members: Arc::from([Arc::from("Student 3"), Arc::from("Nobody")]),
+    };
+    repository.set_group("user", &group).await?;
+
+    let students = repository.get_students("user").await?;
+    assert_eq!(students[0].banned_pairings[..], [Arc::from("Student 1")]);
+    assert_eq!(students[1].banned_pairings[..], [Arc::from("Student 0")]);
+    assert_eq!(students[2].preferred_pairings[..], [Arc::from("Student 3")]);
+    let groups = repository.get_groups("user").await?;
+    assert_eq!(groups[0].members[..], [Arc::from("Student 3")]);
+
+    repository.clear_people("user").await?;
+    assert!(repository.get_people("user").await?.is_empty());
+    assert!(repository.get_preferences("user").await?.is_empty());
+    assert!(repository.get_groups("user").await?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn bans_of_a_name_shared_by_a_teacher_and_a_student_ban_the_teacher() -> Result<(), Error> {
+    for repository in repositories() {
+        add_people(&*repository, "user").await?;
+        repository
+            .add_teachers("user", &[Teacher::new("Sam Lee")])
+            .await?;
+        let sam = Student {
+            name: Arc::from("Sam Lee"),
+            ..Default::default()
+        };
+        repository.add_students("user", &[sam]).await?;
+
+        let named = |name: &str| Person {
+            name: Arc::from(name),
+            banned_pairings: Arc::from([]),
+        };
+        repository
+            .ban_pair("user", [named("Student 0"), named("Sam Lee")])
+            .await?;
+
+        let students = repository.get_students("user").await?;
+        let student = students.iter().find(|s| &*s.name == "Student 0").unwrap();
+        assert!(student.banned_pairings.is_empty());
+        assert_eq!(student.banned_advisors[..], [Arc::from("Sam Lee")]);
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn saved_organizations_round_trip() -> Result<(), Error> {
+    for repository in repositories() {
+        saved_organizations_round_trip_in(&*repository).await?;
+    }
+    Ok(())
+}
+
+/// Save, fetch, rename and delete an organization
+async fn saved_organizations_round_trip_in(repository: &dyn Repository) -> Result<(), Error> {
+    add_people(repository, "user").await?;
+
+    let settings = Settings {
+        num_advisories: 2,
+        teacher_groupings: Arc::from([
+            TeacherGrouping::new([Teacher::new("Teacher 0")]),
+            TeacherGrouping::new([Teacher::new("Teacher 1")]),
+        ]),
+        ..Default::default()
+    };
+    let students = repository.get_students("user").await?;
+    let organization = Organization::generate(&settings, students).await?;
+    let version = repository
+        .save_organization("user", Arc::from("First"), &settings, &organization)
+        .await?;
+
+    let versions = repository.list_organizations("user").await?;
+    assert_eq!(versions[..], [version.clone()][..]);
+    let saved = repository.get_organization("user", &version.id).await?;
+    assert_eq!(saved.info, version);
+    assert_eq!(
+        serde_json::to_value(&saved.organization).unwrap(),
+        serde_json::to_value(&organization).unwrap()
+    );
+
+    let renamed = repository
+        .rename_organization("user", &version.id, Arc::from("Renamed"))
+        .await?;
+    assert_eq!(&*renamed.name, "Renamed");
+    assert!(repository
+        .get_organization("other", &version.id)
+        .await
+        .is_err());
+
+    repository.delete_organization("user", &version.id).await?;
+    assert!(repository.list_organizations("user").await?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn accounts_are_copied_between_repositories() -> Result<(), Error> {
+    let memory = MemoryRepository::new();
+    populate(&memory, "user").await?;
+    add_people(&memory, "other").await?;
+
+    let sqlite = SqliteRepository::open(":memory:").expect("unable to open a SQLite database");
+    copy_account(&memory, &sqlite, "user").await?;
+    copy_account(&memory, &sqlite, "user").await?;
+    assert_same_account(&memory, &sqlite, "user").await?;
+    assert!(sqlite.get_people("other").await?.is_empty());
+
+    let copied = MemoryRepository::new();
+    copy_account(&sqlite, &copied, "user").await?;
+    assert_same_account(&sqlite, &copied, "user").await
+}
+
+/// Store everything that an account can have: people with attributes, every kind of ban and request, a
+/// group, grade levels and two saved organizations, one of them renamed
+async fn populate(repository: &dyn Repository, user_id: &str) -> Result<(), Error> {
+    add_people(repository, user_id).await?;
+    let mut student = repository.get_students(user_id).await?[0].clone();
+    student.grade = Grade::SOPHOMORE;
+    student.attributes = [(Arc::from("athlete"), Arc::from("yes"))]
+        .into_iter()
+        .collect();
+    repository.add_students(user_id, &[student]).await?;
+    // names are unique, so the student's grade is changed rather than a second student being added
+    assert_eq!(repository.get_students(user_id).await?.len(), 4);
+    let levels = GradeLevels {
+        levels: Arc::from([Grade::FRESHMAN, Grade::SOPHOMORE]),
+    };
+    repository.set_grade_levels(user_id, &levels).await?;
+
+    let student = |name: &str| {
+        Person::from(Student {
+            name: Arc::from(name),
+            ..Default::default()
+        })
+    };
+    let teacher = |name: &str| Person::from(Teacher::new(name));
+    repository
+        .ban_pair(user_id, [student("Student 0"), student("Student 1")])
+        .await?;
+    repository
+        .ban_pair(user_id, [student("Student 2"), teacher("Teacher 0")])
+        .await?;
+    repository
+        .ban_pair(user_id, [teacher("Teacher 0"), teacher("Teacher 1")])
+        .await?;
+    let preference = Preference {
+        student: Arc::from("Student 0"),
+        preferred: Arc::from("Student 2"),
+    };
+    repository.add_preference(user_id, &preference).await?;
+    let preference = AdvisorPreference {
+        student: Arc::from("Student 1"),
+        advisor: Arc::from("Teacher 1"),
+    };
+    repository
+        .add_advisor_preference(user_id, &preference)
+        .await?;
+    let group = Group {
+        name: Arc::from("Siblings"),
+        members: Arc::from([Arc::from("Student 2"), Arc::from("Student 3")]),
+    };
+    repository.set_group(user_id, &group).await?;
+
+    let settings = Settings {
+        num_advisories: 2,
+        teacher_groupings: Arc::from([
+            TeacherGrouping::new([Teacher::new("Teacher 0")]),
+            TeacherGrouping::new([Teacher::new("Teacher 1")]),
+        ]),
+        grade_levels: Some(levels),
+        ..Default::default()
+    };
+    let organization =
+        Organization::generate(&settings, repository.get_students(user_id).await?).await?;
+    repository
+        .save_organization(user_id, Arc::from("First"), &settings, &organization)
+        .await?;
+    let second = repository
+        .save_organization(user_id, Arc::from("Second"), &settings, &organization)
+        .await?;
+    repository
+        .rename_organization(user_id, &second.id, Arc::from("Renamed"))
+        .await?;
+    Ok(())
+}
+
+/// Check that two repositories store the same things for an account
+async fn assert_same_account(
+    a: &dyn Repository,
+    b: &dyn Repository,
+    user_id: &str,
+) -> Result<(), Error> {
+    assert_eq!(a.get_people(user_id).await?, b.get_people(user_id).await?);
+    assert_eq!(
+        a.get_teachers(user_id).await?,
+        b.get_teachers(user_id).await?
+    );
+    assert_eq!(
+        a.get_students(user_id).await?,
+        b.get_students(user_id).await?
+    );
+    assert_eq!(
+        a.get_coadvisor_bans(user_id).await?,
+        b.get_coadvisor_bans(user_id).await?
+    );
+    assert_eq!(
+        a.get_preferences(user_id).await?,
+        b.get_preferences(user_id).await?
+    );
+    assert_eq!(
+        a.get_advisor_preferences(user_id).await?,
+        b.get_advisor_preferences(user_id).await?
+    );
+    assert_eq!(a.get_groups(user_id).await?, b.get_groups(user_id).await?);
+    assert_eq!(
+        a.get_grade_levels(user_id).await?,
+        b.get_grade_levels(user_id).await?
+    );
+    let versions = a.list_organizations(user_id).await?;
+    assert_eq!(versions.len(), 2);
+    assert_eq!(versions, b.list_organizations(user_id).await?);
+    for version in versions.iter() {
+        assert_eq!(
+            serde_json::to_value(a.get_organization(user_id, &version.id).await?).unwrap(),
+            serde_json::to_value(b.get_organization(user_id, &version.id).await?).unwrap()
+        );
+    }
+    Ok(())
+}
@@ -3,11 +3,23 @@ import axios, { type AxiosResponse } from 'axios';
 import { id_token } from '$lib/auth_store';
 import type {
     Teacher,
+    GeneratedOrganization,
+    Grade,
+    Group,
     Advisory,
+    Edit,
+    EditResult,
+    OrganizationDiff,
+    OrganizationSource,
+    Regeneration,
+    SavedOrganization,
     Student,
     Weights,
     Settings,
     Person,
+    Preference,
+    AdvisorPreference,
+    VersionInfo,
 } from '$lib/DBTypes';
 
 let auth: string;
@@ -91,6 +103,111 @@ export default class API {
         });
     }
 
+    static add_preference(
+        preference: Preference
+    ): Promise<AxiosResponse<number, any>> {
+        return axios<number>({
+            method: 'post',
+            url: `${this.BASE_URL}/people/prefer`,
+            data: preference,
+            headers: {
+                Authorization: auth,
+            },
+        });
+    }
+
+    static list_preferences(): Promise<AxiosResponse<Preference[], any>> {
+        return axios<Preference[]>({
+            method: 'get',
+            url: `${this.BASE_URL}/people/prefer`,
+            headers: {
+                Authorization: auth,
+            },
+        });
+    }
+
+    static remove_preference(
+        preference: Preference
+    ): Promise<AxiosResponse<number, any>> {
+        return axios<number>({
+            method: 'delete',
+            url: `${this.BASE_URL}/people/prefer`,
+            data: preference,
+            headers: {
+                Authorization: auth,
+            },
+        });
+    }
+
+    static add_advisor_preference(
+        preference: AdvisorPreference
+    ): Promise<AxiosResponse<number, any>> {
+        return axios<number>({
+            method: 'post',
+            url: `${this.BASE_URL}/people/prefer/advisor`,
+            data: preference,
+            headers: {
+                Authorization: auth,
+            },
+        });
+    }
+
+    static list_advisor_preferences(): Promise<
+        AxiosResponse<AdvisorPreference[], any>
+    > {
+        return axios<AdvisorPreference[]>({
+            method: 'get',
+            url: `${this.BASE_URL}/people/prefer/advisor`,
+            headers: {
+                Authorization: auth,
+            },
+        });
+    }
+
+    static remove_advisor_preference(
+        preference: AdvisorPreference
+    ): Promise<AxiosResponse<number, any>> {
+        return axios<number>({
+            method: 'delete',
+            url: `${this.BASE_URL}/people/prefer/advisor`,
+            data: preference,
+            headers: {
+                Authorization: auth,
+            },
+        });
+    }
+
+    static set_group(group: Group): Promise<AxiosResponse<number, any>> {
+        return axios<number>({
+            method: 'post',
+            url: `${this.BASE_URL}/people/groups`,
+            data: group,
+            headers: {
+                Authorization: auth,
+            },
+        });
+    }
+
+    static list_groups(): Promise<AxiosResponse<Group[], any>> {
+        return axios<Group[]>({
+            method: 'get',
+            url: `${this.BASE_URL}/people/groups`,
+            headers: {
+                Authorization: auth,
+            },
+        });
+    }
+
+    static remove_group(name: string): Promise<AxiosResponse<number, any>> {
+        return axios<number>({
+            method: 'delete',
+            url: `${this.BASE_URL}/people/groups/${encodeURIComponent(name)}`,
+            headers: {
+                Authorization: auth,
+            },
+        });
+    }
+
     static list_people(): Promise<AxiosResponse<any, any>> {
         return axios<Person[]>({
             method: 'get',
@@ -111,6 +228,29 @@ export default class API {
         });
     }
 
+    static get_grade_levels(): Promise<AxiosResponse<Grade[], any>> {
+        return axios<Grade[]>({
+            method: 'get',
+            url: `${this.BASE_URL}/people/grades`,
+            headers: {
+                Authorization: auth,
+            },
+        });
+    }
+
+    static set_grade_levels(
+        levels: Grade[]
+    ): Promise<AxiosResponse<number, any>> {
+        return axios<number>({
+            method: 'put',
+            url: `${this.BASE_URL}/people/grades`,
+            data: levels,
+            headers: {
+                Authorization: auth,
+            },
+        });
+    }
+
     static list_students(): Promise<AxiosResponse<any, any>> {
         return axios<Student[]>({
             method: 'get',
@@ -124,14 +264,14 @@ export default class API {
     static get_advisories(
         teacher_groupings: Teacher[][],
         weights: Weights
-    ): Promise<AxiosResponse<Advisory[], any>> {
+    ): Promise<AxiosResponse<GeneratedOrganization, any>> {
         const data: Settings = {
             weights,
             num_advisories: teacher_groupings.length,
             teacher_groupings,
         };
 
-        return axios<Advisory[]>({
+        return axios<GeneratedOrganization>({
             method: 'put',
             url: `${this.BASE_URL}/`,
             data,
@@ -140,4 +280,117 @@ export default class API {
             },
         });
     }
+
+    static regenerate_advisories(
+        previous: Advisory[],
+        teacher_groupings: Teacher[][],
+        weights: Weights
+    ): Promise<AxiosResponse<Regeneration, any>> {
+        const settings: Settings = {
+            weights,
+            num_advisories: teacher_groupings.length,
+            teacher_groupings,
+        };
+
+        return axios<Regeneration>({
+            method: 'put',
+            url: `${this.BASE_URL}/regenerate`,
+            data: { settings, previous },
+            headers: {
+                Authorization: auth,
+            },
+        });
+    }
+
+    static list_organizations(): Promise<AxiosResponse<VersionInfo[], any>> {
+        return axios<VersionInfo[]>({
+            method: 'get',
+            url: `${this.BASE_URL}/organizations`,
+            headers: {
+                Authorization: auth,
+            },
+        });
+    }
+
+    static save_organization(
+        name: string,
+        settings: Settings,
+        organization: Advisory[]
+    ): Promise<AxiosResponse<VersionInfo, any>> {
+        return axios<VersionInfo>({
+            method: 'post',
+            url: `${this.BASE_URL}/organizations`,
+            data: { name, settings, organization },
+            headers: {
+                Authorization: auth,
+            },
+        });
+    }
+
+    static get_organization(
+        id: string
+    ): Promise<AxiosResponse<SavedOrganization, any>> {
+        return axios<SavedOrganization>({
+            method: 'get',
+            url: `${this.BASE_URL}/organizations/${encodeURIComponent(id)}`,
+            headers: {
+                Authorization: auth,
+            },
+        });
+    }
+
+    static edit_organization(
+        id: string,
+        edit: Edit
+    ): Promise<AxiosResponse<EditResult, any>> {
+        return axios<EditResult>({
+            method: 'post',
+            url: `${this.BASE_URL}/organizations/${encodeURIComponent(id)}/edits`,
+            data: edit,
+            headers: {
+                Authorization: auth,
+            },
+        });
+    }
+
+    static diff_organizations(
+        before: OrganizationSource,
+        after: OrganizationSource,
+        weights: Weights
+    ): Promise<AxiosResponse<OrganizationDiff, any>> {
+        return axios<OrganizationDiff>({
+            method: 'put',
+            url: `${this.BASE_URL}/diff`,
+            data: { before, after, weights },
+            headers: {
+                Authorization: auth,
+            },
+        });
+    }
+
+    static rename_organization(
+        id: string,
+        name: string
+    ): Promise<AxiosResponse<VersionInfo, any>> {
+        return axios<VersionInfo>({
+            method: 'patch',
+            url: `${this.BASE_URL}/organizations/${encodeURIComponent(id)}`,
+            data: { name },
+            headers: {
+                Authorization: auth,
+            },
+        });
+    }
+
+    static delete_organization(
+        id: string
+    ): Promise<AxiosResponse<number, any>> {
+        return axios<number>({
+            method: 'delete',
+            url: `${this.BASE_URL}/organizations/${encodeURIComponent(id)}`,
+            headers: {
+                Authorization: auth,
+            },
+        });
+    }
 }
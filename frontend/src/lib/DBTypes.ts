@@ -1,13 +1,13 @@
-export enum Sex {
-    Male = 'Male',
-    Female = 'Female',
-}
-export enum Grade {
-    Freshman = 'Freshman',
-    Sophomore = 'Sophomore',
-    Junior = 'Junior',
-    Senior = 'Senior',
-}
+// name of the attribute holding a student's gender
+export const GENDER = 'gender';
+// numeric grade level, with kindergarten as 0
+export type Grade = number;
+export const Grade = {
+    Freshman: 9,
+    Sophomore: 10,
+    Junior: 11,
+    Senior: 12,
+} as const;
 export interface Teacher {
     name: string;
 }
@@ -17,29 +17,206 @@ export interface Person {
 }
 export interface Student {
     name: string;
-    sex: Sex;
+    // demographic attributes by name, such as gender, house or campus
+    attributes: Record<string, string>;
     teachers: Teacher[];
     grade: Grade;
     banned_pairings: string[];
+    banned_advisors?: string[];
+    preferred_pairings?: string[];
+    preferred_advisors?: string[];
+}
+
+// request from a student to be placed with a peer
+export interface Preference {
+    student: string;
+    preferred: string;
+}
+
+// request from a student to be advised by a particular teacher
+export interface AdvisorPreference {
+    student: string;
+    advisor: string;
 }
 
 export interface Advisory {
     // user_id: string;
     advisors: Teacher[];
     students: Student[];
-    // remaining_sex: [number, number];
     // remaining_grade: [number, number, number, number];
 }
 
+export interface Score {
+    teacher: number;
+    sex: number;
+    attributes: number;
+    criteria: number;
+    grade: number;
+    people: number;
+    banned: number;
+    preferred: number;
+    preferred_advisor: number;
+    stability: number;
+    total: number;
+}
+
+export interface Group {
+    name: string;
+    members: string[];
+}
+
+export interface PreferenceReport {
+    requested: number;
+    honored: number;
+    advisors_requested: number;
+    advisors_honored: number;
+}
+
+export interface OrganizationScore {
+    total: Score;
+    advisories: Score[];
+    preferences: PreferenceReport;
+}
+
+export interface RunScore {
+    seed: number;
+    total: number | null;
+}
+
+export interface GeneratedOrganization {
+    organization: Advisory[];
+    score: OrganizationScore;
+    seed: number;
+    runs: RunScore[];
+    bound: number | null;
+    version?: VersionInfo | null;
+    // advisor groupings formed by the backend when none were sent
+    teacher_groupings?: (Teacher[] | TeacherGrouping)[] | null;
+}
+
+export interface VersionInfo {
+    id: string;
+    name: string;
+    created: string;
+}
+
+export interface SavedOrganization extends VersionInfo {
+    settings: Settings;
+    organization: Advisory[];
+}
+
+export type Edit =
+    | { kind: 'move_student'; student: string; to: number }
+    | { kind: 'swap_students'; first: string; second: string }
+    | { kind: 'move_advisor'; advisor: string; to: number };
+
+export type Violation =
+    | {
+          kind: 'banned_pairing';
+          advisory: number;
+          student: string;
+          with: string[];
+      }
+    | {
+          kind: 'banned_advisor';
+          advisory: number;
+          student: string;
+          advisors: string[];
+      }
+    | { kind: 'over_capacity'; advisory: number; students: number; max: number }
+    | {
+          kind: 'under_capacity';
+          advisory: number;
+          students: number;
+          min: number;
+      };
+
+export type OrganizationSource = { id: string } | Advisory[];
+
+export interface AdvisorChange {
+    before: string[];
+    after: string[];
+}
+
+export interface OrganizationDiff {
+    moved: Move[];
+    added: string[];
+    removed: string[];
+    advisors: AdvisorChange[];
+    score: Score;
+}
+
+export interface EditResult {
+    organization: Advisory[];
+    score: OrganizationScore;
+    violations: Violation[];
+    introduced: Violation[];
+}
+
+export interface Move {
+    student: string;
+    from: string[];
+    to: string[];
+}
+
+export interface Regeneration extends GeneratedOrganization {
+    moved: Move[];
+    added: string[];
+    removed: string[];
+}
+
 export interface Weights {
     has_teacher: number;
     sex_diverse: number;
     grade_diverse: number;
     equal_people: number;
+    stability?: number;
+    preferred?: number;
+    preferred_advisor?: number;
+    // weights of other attributes to balance, by name
+    attributes?: Record<string, number>;
+    criteria?: Criterion[];
+}
+
+export type FieldKind = 'categorical' | 'numeric';
+
+export type BalanceRule = { rule: 'spread' } | { rule: 'cluster' } | { rule: 'at_most'; max: number };
+
+// custom rule for balancing a student attribute across advisories
+export type Criterion = BalanceRule & {
+    field: string;
+    kind?: FieldKind;
+    weight: number;
+};
+
+export interface TeacherGrouping {
+    teachers: Teacher[];
+    min_students?: number | null;
+    max_students?: number | null;
 }
 
 export interface Settings {
     weights: Weights;
     num_advisories: number;
-    teacher_groupings: Teacher[][];
+    // omit to have the backend group advisors automatically
+    teacher_groupings?: (Teacher[] | TeacherGrouping)[];
+    grade_levels?: Grade[];
+    groups?: Group[];
+}
+
+// problem found with one field of a request, such as a student in a bulk upload without a name
+export interface Problem {
+    field: string;
+    index?: number;
+    name?: string;
+    message: string;
+}
+
+// body of every error response from the backend
+export interface ProblemDetails {
+    type: string;
+    title: string;
+    status: number;
+    detail: string;
+    problems?: Problem[];
 }
@@ -1,4 +1,4 @@
-import { type Teacher, type Student, Sex, Grade } from '$lib/DBTypes';
+import { type Teacher, type Student, GENDER, Grade } from '$lib/DBTypes';
 import { read, utils, type WorkSheet, type WorkBook } from 'xlsx';
 
 type Table = string[][];
@@ -35,7 +35,7 @@ export function get_students(table: Table): Set<Student> {
         name: '',
         teachers: [],
         grade: Grade.Freshman,
-        sex: Sex.Male,
+        attributes: {},
         banned_pairings: [],
     };
     const current_student_teachers = new Set<string>();
@@ -45,7 +45,7 @@ export function get_students(table: Table): Set<Student> {
         const row_empty: boolean = row[0] == undefined;
         const row_name: string = row[6] + ' ' + row[5];
         const row_grade: Grade = parse_grade_string(row[4]);
-        const row_sex: Sex = row[9] as Sex;
+        const row_gender: string | undefined = row[9];
         const row_teacher_name: string = row[8];
 
         if (row_empty && previous_row_empty) {
@@ -66,7 +66,7 @@ export function get_students(table: Table): Set<Student> {
                 name: row_name,
                 teachers: [],
                 grade: row_grade,
-                sex: row_sex,
+                attributes: row_gender ? { [GENDER]: row_gender } : {},
                 banned_pairings: [],
             };
         }
@@ -78,26 +78,8 @@ export function get_students(table: Table): Set<Student> {
 }
 
 function parse_grade_string(grade: string): Grade {
-    let value: Grade = Grade.Freshman;
-    switch (parseInt(grade)) {
-        case 9: {
-            value = Grade.Freshman;
-            break;
-        }
-        case 10: {
-            value = Grade.Sophomore;
-            break;
-        }
-        case 11: {
-            value = Grade.Junior;
-            break;
-        }
-        case 12: {
-            value = Grade.Senior;
-            break;
-        }
-    }
-    return value;
+    const value = parseInt(grade);
+    return isNaN(value) ? Grade.Freshman : value;
 }
 
 export function import_table(buffer: ArrayBuffer): Table {